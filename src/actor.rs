@@ -1,6 +1,176 @@
+use std::error::Error;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
 use crate::game::action::Action;
+use crate::game::game_result::GameResult;
 use crate::game::game_state::GameState;
+use crate::game::player::Player;
+use crate::game::record::GameRecord;
+
+/// What an actor wants to do on its turn.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Decision {
+    Move(Action),
+    /// Take back the actor's last move and the reply to it.
+    Undo,
+    Resign,
+    /// Ask the opponent to agree to a draw. If they decline, it is still the
+    /// actor's turn.
+    OfferDraw,
+    /// Stop the game without a result.
+    Quit,
+}
+
+/// Why an actor could not decide.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ActorError {
+    /// The actor's connection or input went away.
+    Disconnected(String),
+    /// The actor's clock ran out.
+    Timeout,
+    /// The game was cancelled while the actor was thinking.
+    Cancelled,
+    Failed(String),
+}
+
+impl fmt::Display for ActorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ActorError::Disconnected(reason) => write!(f, "disconnected: {}", reason),
+            ActorError::Timeout => write!(f, "out of time"),
+            ActorError::Cancelled => write!(f, "cancelled"),
+            ActorError::Failed(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+impl Error for ActorError {}
+
+/// What a searching actor found while choosing its last move.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SearchReport {
+    pub simulations: usize,
+    /// The expected score of the move for the player making it.
+    pub expected: f32,
+    /// The result the move forces, when the actor proved it.
+    pub proven: Option<GameResult>,
+}
+
+/// The time an actor has for its decision, counted from when it was asked.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Clock {
+    pub started: Instant,
+    /// No limit if `None`.
+    pub limit: Option<Duration>,
+}
+
+impl Clock {
+    pub fn new(limit: Option<Duration>) -> Clock {
+        Clock { started: Instant::now(), limit }
+    }
+
+    pub fn unlimited() -> Clock {
+        Clock::new(None)
+    }
+
+    pub fn remaining(&self) -> Option<Duration> {
+        self.limit.map(|limit| limit.saturating_sub(self.started.elapsed()))
+    }
+
+    pub fn expired(&self) -> bool {
+        self.remaining() == Some(Duration::from_millis(0))
+    }
+}
+
+/// Lets whoever runs a game stop it, including an actor that is still
+/// thinking. Clones share the same flag.
+#[derive(Clone, Default, Debug)]
+pub struct CancelToken(Arc<AtomicBool>);
 
+impl CancelToken {
+    pub fn new() -> CancelToken {
+        CancelToken::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Everything an actor is told besides the position.
+#[derive(Clone)]
+pub struct Context<'a> {
+    /// The game so far: the players, the initial position and the moves.
+    pub record: &'a GameRecord,
+    pub clock: Clock,
+    pub cancel: CancelToken,
+}
+
+impl<'a> Context<'a> {
+    pub fn new(record: &'a GameRecord) -> Context<'a> {
+        Context { record, clock: Clock::unlimited(), cancel: CancelToken::new() }
+    }
+
+    pub fn with_clock(mut self, clock: Clock) -> Context<'a> {
+        self.clock = clock;
+        self
+    }
+
+    pub fn with_cancel(mut self, cancel: CancelToken) -> Context<'a> {
+        self.cancel = cancel;
+        self
+    }
+
+    /// Whether the actor should stop thinking and decide now.
+    pub fn should_stop(&self) -> bool {
+        self.cancel.is_cancelled() || self.clock.expired()
+    }
+}
+
+/// A player of games: an engine, a person at the keyboard, a remote peer.
+/// Actors keep whatever state they like between moves. `Match` tells them
+/// when a game starts, what their opponent plays and when the game is over;
+/// taking back moves is not announced, so actors that track the position
+/// should check it against the one they are asked about.
 pub trait Actor {
-    fn get_action(&self, game_state: &mut GameState) -> Action;
+    /// A game starts from `game_state`, with the actor playing `player`.
+    fn new_game(&mut self, _game_state: &GameState, _player: Player) {}
+
+    /// Decides on a turn given the game so far. Actors that can fail, such as
+    /// remote players, or that can be interrupted say so with an error.
+    fn get_decision(&mut self, game_state: &GameState, context: &Context) -> Result<Decision, ActorError>;
+
+    /// How strongly the actor considers each move, summing to 1. Search-based
+    /// actors report their visit distribution; by default the move the actor
+    /// decides on gets all the weight, and any other decision is an error.
+    fn get_policy(&mut self, game_state: &GameState, context: &Context) -> Result<Vec<(Action, f32)>, ActorError> {
+        match self.get_decision(game_state, context)? {
+            Decision::Move(action) => Ok(vec![(action, 1.)]),
+            decision => Err(ActorError::Failed(format!("expected a move, not {:?}", decision))),
+        }
+    }
+
+    /// The report on the search behind the last move, for actors that search.
+    fn search_report(&self) -> Option<SearchReport> {
+        None
+    }
+
+    /// Answers the opponent's offer of a draw in this position. By default
+    /// offers are declined.
+    fn accept_draw(&mut self, _game_state: &GameState, _context: &Context) -> Result<bool, ActorError> {
+        Ok(false)
+    }
+
+    /// The opponent played `action`, which led to `game_state`.
+    fn opponent_moved(&mut self, _action: &Action, _game_state: &GameState) {}
+
+    /// The game is over, finished or not: see the record's result.
+    fn game_over(&mut self, _record: &GameRecord) {}
 }
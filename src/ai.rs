@@ -1 +1,11 @@
+pub mod analysis;
+pub mod baseline;
+pub mod book;
+pub mod endgame;
+pub mod evaluator;
 pub mod mcts;
+pub mod nn;
+pub mod puct;
+pub mod selfplay;
+pub mod tournament;
+pub mod training;
@@ -0,0 +1,90 @@
+use crate::ai::mcts::MCTSActor;
+use crate::game::action::Action;
+use crate::game::player::Player;
+use crate::game::record::GameRecord;
+
+/// How much of their expected score a move must cost a player to count as a
+/// mistake.
+pub const MISTAKE: f32 = 0.1;
+/// How much of their expected score a move must cost a player to count as a
+/// blunder.
+pub const BLUNDER: f32 = 0.25;
+
+/// The engine's view of one move.
+#[derive(Clone, PartialEq, Debug)]
+pub struct MoveAnalysis {
+    pub action: Action,
+    /// The share of the search spent on the move.
+    pub share: f32,
+    /// The expected score of the move for the player making it: the chance to
+    /// win, with draws counting half.
+    pub score: f32,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Judgement {
+    Good,
+    Mistake,
+    Blunder,
+}
+
+impl Judgement {
+    /// Judges a move by how much of their expected score it cost the player.
+    pub fn from_loss(loss: f32) -> Judgement {
+        if loss >= BLUNDER {
+            Judgement::Blunder
+        } else if loss >= MISTAKE {
+            Judgement::Mistake
+        } else {
+            Judgement::Good
+        }
+    }
+}
+
+/// A move of a reviewed game, compared to what the engine would have played.
+#[derive(Clone, PartialEq, Debug)]
+pub struct MoveReview {
+    pub action: Action,
+    pub player: Player,
+    /// The engine's choice and the expected score it gives the player.
+    pub best: MoveAnalysis,
+    /// The expected score for the player after the move that was played.
+    pub score: f32,
+    pub judgement: Judgement,
+}
+
+impl MoveReview {
+    /// The expected score the move cost compared to the engine's choice.
+    pub fn loss(&self) -> f32 {
+        (self.best.score - self.score).max(0.)
+    }
+}
+
+/// Scores every position of a game with `engine` and judges each move by how
+/// much it lowered the expected score of the player who made it.
+pub fn review(record: &GameRecord, engine: &MCTSActor) -> Vec<MoveReview> {
+    let mut game_state = record.initial_state.clone();
+    let mut analysis = engine.analyse(&game_state);
+    let mut reviews = Vec::with_capacity(record.moves.len());
+
+    for action in record.moves.iter() {
+        let player = game_state.current_player();
+        let best = analysis[0].clone();
+
+        let score = match action.apply(&mut game_state) {
+            Some(result) => result.score(player),
+            None => {
+                analysis = engine.analyse(&game_state);
+                // Searches of consecutive positions disagree a little; the
+                // engine's own choice never counts as a loss.
+                if *action == best.action { best.score } else { 1. - analysis[0].score }
+            },
+        };
+
+        let mut review = MoveReview { action: action.clone(), player, best, score, judgement: Judgement::Good };
+        review.judgement = Judgement::from_loss(review.loss());
+        reviews.push(review);
+    }
+
+    reviews
+}
@@ -0,0 +1,182 @@
+use rand::prelude::*;
+use rand::rngs::StdRng;
+
+use crate::actor::{Actor, ActorError, Context, Decision};
+use crate::game::action::Action;
+use crate::game::board::Owned;
+use crate::game::game_result::GameResult;
+use crate::game::game_state::GameState;
+use crate::game::player::Player;
+use crate::game::record::GameRecord;
+
+fn rng(seed: Option<u64>) -> StdRng {
+    match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    }
+}
+
+/// Plays a uniformly random legal move.
+pub struct RandomActor {
+    rng: StdRng,
+}
+
+impl RandomActor {
+    pub fn new() -> RandomActor {
+        RandomActor { rng: rng(None) }
+    }
+
+    /// Plays the same moves in the same positions on every run.
+    pub fn with_seed(mut self, seed: u64) -> RandomActor {
+        self.rng = rng(Some(seed));
+        self
+    }
+}
+
+impl Default for RandomActor {
+    fn default() -> RandomActor {
+        RandomActor::new()
+    }
+}
+
+impl Actor for RandomActor {
+    fn get_decision(&mut self, game_state: &GameState, _context: &Context) -> Result<Decision, ActorError> {
+        Ok(Decision::Move(game_state.possible_actions().choose(&mut self.rng).unwrap().clone()))
+    }
+}
+
+fn sub_boards_won(game_state: &GameState, player: Player) -> usize {
+    game_state.board().structure().items().iter().filter(|sub_board| sub_board.result() == Some(player.wins())).count()
+}
+
+/// Whether the player to move can win the game or a sub-board right away.
+fn threatens(game_state: &GameState) -> bool {
+    let player = game_state.current_player();
+    let won = sub_boards_won(game_state, player);
+
+    game_state.possible_actions().iter().any(|action| {
+        let mut next = game_state.clone();
+        action.apply(&mut next) == Some(player.wins()) || sub_boards_won(&next, player) > won
+    })
+}
+
+/// Looks one move ahead: wins the game if it can, otherwise wins a sub-board,
+/// and avoids moves that let the opponent win one. Ties are broken at random.
+pub struct GreedyActor {
+    rng: StdRng,
+}
+
+impl GreedyActor {
+    pub fn new() -> GreedyActor {
+        GreedyActor { rng: rng(None) }
+    }
+
+    /// Breaks ties the same way on every run.
+    pub fn with_seed(mut self, seed: u64) -> GreedyActor {
+        self.rng = rng(Some(seed));
+        self
+    }
+
+    /// How much the move appeals, higher being better.
+    fn score(game_state: &GameState, action: &Action) -> i32 {
+        let player = game_state.current_player();
+        let mut next = game_state.clone();
+
+        match action.apply(&mut next) {
+            Some(result) if result == player.wins() => 4,
+            Some(GameResult::Draw) => 0,
+            Some(_) => -1,
+            None => {
+                let gains = sub_boards_won(&next, player) > sub_boards_won(game_state, player);
+                2 * gains as i32 + !threatens(&next) as i32
+            },
+        }
+    }
+}
+
+impl Default for GreedyActor {
+    fn default() -> GreedyActor {
+        GreedyActor::new()
+    }
+}
+
+impl Actor for GreedyActor {
+    fn get_decision(&mut self, game_state: &GameState, _context: &Context) -> Result<Decision, ActorError> {
+        let scored: Vec<(Action, i32)> = game_state.possible_actions()
+            .into_iter()
+            .map(|action| {
+                let score = GreedyActor::score(game_state, &action);
+                (action, score)
+            })
+            .collect();
+        let best = scored.iter().map(|(_, score)| *score).max().unwrap();
+
+        let candidates: Vec<Action> = scored.into_iter().filter(|(_, score)| *score == best).map(|(action, _)| action).collect();
+        Ok(Decision::Move(candidates.choose(&mut self.rng).unwrap().clone()))
+    }
+}
+
+/// Replays the moves of a game record for whichever side it plays, and ends
+/// the game as the record did: by resigning or agreeing to a draw if its
+/// moves stop short of the result. Positions that are not in the record are
+/// an error, as is a draw offer that is turned down: the script has nothing
+/// left to play then.
+pub struct ScriptedActor {
+    record: GameRecord,
+    /// The position before each move of the record, and after the last.
+    positions: Vec<GameState>,
+    /// Whether the recorded draw was offered in this game.
+    offered: bool,
+}
+
+impl ScriptedActor {
+    pub fn new(record: GameRecord) -> ScriptedActor {
+        let mut game_state = record.initial_state.clone();
+        let mut positions = vec![game_state.clone()];
+        for action in record.moves.iter() {
+            action.apply(&mut game_state);
+            positions.push(game_state.clone());
+        }
+
+        ScriptedActor { record, positions, offered: false }
+    }
+
+    /// How many moves of the record lead to this position.
+    fn find(&self, game_state: &GameState) -> Result<usize, ActorError> {
+        self.positions.iter()
+            .position(|position| position == game_state)
+            .ok_or_else(|| ActorError::Failed("the position is not in the script".to_string()))
+    }
+
+    fn decide(&mut self, game_state: &GameState) -> Result<Decision, ActorError> {
+        let index = self.find(game_state)?;
+        if let Some(action) = self.record.moves.get(index) {
+            return Ok(Decision::Move(action.clone()));
+        }
+
+        match self.record.result {
+            Some(GameResult::Draw) if self.offered => Err(ActorError::Failed("the script is exhausted".to_string())),
+            Some(GameResult::Draw) => {
+                self.offered = true;
+                Ok(Decision::OfferDraw)
+            },
+            Some(result) if result != game_state.current_player().wins() => Ok(Decision::Resign),
+            _ => Err(ActorError::Failed("the script has no move here".to_string())),
+        }
+    }
+}
+
+impl Actor for ScriptedActor {
+    fn new_game(&mut self, _game_state: &GameState, _player: Player) {
+        self.offered = false;
+    }
+
+    fn get_decision(&mut self, game_state: &GameState, _context: &Context) -> Result<Decision, ActorError> {
+        self.decide(game_state)
+    }
+
+    fn accept_draw(&mut self, game_state: &GameState, _context: &Context) -> Result<bool, ActorError> {
+        let index = self.find(game_state)?;
+        Ok(index == self.record.moves.len() && self.record.result == Some(GameResult::Draw))
+    }
+}
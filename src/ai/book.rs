@@ -0,0 +1,357 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use rand::distributions::WeightedIndex;
+use rand::prelude::*;
+
+use crate::actor::{Actor, ActorError, Context, Decision, SearchReport};
+use crate::ai::evaluator::Evaluator;
+use crate::ai::mcts::{search, Limit};
+use crate::ai::nn::{cell_action, cell_index};
+use crate::game::action::Action;
+use crate::game::board::Owned;
+use crate::game::game_result::GameResult;
+use crate::game::game_state::GameState;
+use crate::game::notation::{from_notation, to_notation};
+use crate::game::player::Player;
+use crate::game::record::GameRecord;
+use crate::game::rules::Rules;
+use crate::game::symmetry::canonicalize;
+
+const MAGIC: &[u8; 4] = b"UTBK";
+const VERSION: u32 = 1;
+
+/// Moves with weights for positions near the start of the game. Positions are
+/// stored in their canonical form, so one entry covers all of its symmetric
+/// images. Books only cover the standard rules: positions played under other
+/// rules are neither stored nor looked up.
+#[derive(Default)]
+pub struct OpeningBook {
+    entries: HashMap<String, Vec<(Action, u32)>>,
+}
+
+impl OpeningBook {
+    pub fn new() -> OpeningBook {
+        OpeningBook { entries: HashMap::new() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn insert(&mut self, game_state: &GameState, moves: Vec<(Action, u32)>) {
+        if *game_state.rules() != Rules::new() {
+            return;
+        }
+
+        let (canonical, symmetry) = canonicalize(game_state);
+        let size = game_state.board().size();
+        let moves = moves.iter()
+            .map(|(action, weight)| (symmetry.transform_action(action, size), *weight))
+            .collect();
+
+        self.entries.insert(to_notation(&canonical), moves);
+    }
+
+    /// The book moves for a position, translated back to its orientation.
+    pub fn lookup(&self, game_state: &GameState) -> Option<Vec<(Action, u32)>> {
+        if *game_state.rules() != Rules::new() {
+            return None;
+        }
+
+        let (canonical, symmetry) = canonicalize(game_state);
+        let inverse = symmetry.inverse();
+        let size = game_state.board().size();
+
+        self.entries.get(&to_notation(&canonical)).map(|moves| {
+            moves.iter().map(|(action, weight)| (inverse.transform_action(action, size), *weight)).collect()
+        })
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<OpeningBook> {
+        OpeningBook::read(&mut BufReader::new(File::open(path)?))
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write(&mut writer)?;
+        writer.flush()
+    }
+
+    /// Reads a book in the binary format: the magic `UTBK`, a format version and
+    /// the number of entries, all little-endian 32-bit values, followed by the
+    /// entries. An entry is the 81 squares row by row at 2 bits each (0 empty,
+    /// 1 player 1, 2 player 2) in 21 bytes, a byte holding the player to move
+    /// in its high nibble and the forced sub-board (9 for none) in its low
+    /// nibble, the number of moves as a byte, and every move as a cell byte
+    /// with a little-endian 16-bit weight. The format only covers the standard
+    /// board.
+    pub fn read<R: Read>(reader: &mut R) -> io::Result<OpeningBook> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid_data("not an opening book file".to_string()));
+        }
+
+        let mut bytes = [0; 4];
+        reader.read_exact(&mut bytes)?;
+        let version = u32::from_le_bytes(bytes);
+        if version != VERSION {
+            return Err(invalid_data(format!("unsupported book version {}", version)));
+        }
+
+        reader.read_exact(&mut bytes)?;
+        let count = u32::from_le_bytes(bytes);
+
+        let mut book = OpeningBook::new();
+        for _ in 0..count {
+            let mut position = [0; 22];
+            reader.read_exact(&mut position)?;
+            let game_state = decode_position(&position)?;
+            let legal = game_state.possible_actions();
+
+            let mut move_count = [0; 1];
+            reader.read_exact(&mut move_count)?;
+
+            let mut moves = Vec::with_capacity(move_count[0] as usize);
+            for _ in 0..move_count[0] {
+                let mut entry = [0; 3];
+                reader.read_exact(&mut entry)?;
+                let action = legal.iter()
+                    .find(|action| cell_index(action) == entry[0] as usize)
+                    .ok_or_else(|| invalid_data(format!("illegal move to cell {} in {}", entry[0], to_notation(&game_state))))?;
+
+                moves.push((action.clone(), u16::from_le_bytes([entry[1], entry[2]]) as u32));
+            }
+
+            book.entries.insert(to_notation(&game_state), moves);
+        }
+
+        Ok(book)
+    }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        writer.write_all(&(self.entries.len() as u32).to_le_bytes())?;
+
+        let mut keys: Vec<&String> = self.entries.keys().collect();
+        keys.sort();
+
+        for key in keys {
+            let game_state = from_notation(key).map_err(invalid_data)?;
+            if !game_state.rules().is_standard_size() {
+                return Err(invalid_data("only books for the standard board can be written".to_string()));
+            }
+            writer.write_all(&encode_position(&game_state))?;
+
+            let moves = &self.entries[key];
+            writer.write_all(&[moves.len().min(255) as u8])?;
+            for (action, weight) in moves.iter().take(255) {
+                writer.write_all(&[cell_index(action) as u8])?;
+                writer.write_all(&(*weight.min(&(u16::MAX as u32)) as u16).to_le_bytes())?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn encode_position(game_state: &GameState) -> [u8; 22] {
+    let mut bytes = [0; 22];
+
+    for cell in 0..81 {
+        let action = cell_action(cell, false);
+        let value = match game_state.board().get(action.sub_x, action.sub_y).get(action.x, action.y).result() {
+            Some(GameResult::Player1Wins) => 1,
+            Some(GameResult::Player2Wins) => 2,
+            _ => 0,
+        };
+        bytes[cell / 4] |= value << (2 * (cell % 4));
+    }
+
+    let sub_board = match (game_state.current_sub_x, game_state.current_sub_y) {
+        (Some(sub_x), Some(sub_y)) => 3 * sub_y + sub_x,
+        _ => 9,
+    };
+    bytes[21] = ((game_state.current_player().num() as u8) << 4) | sub_board as u8;
+
+    bytes
+}
+
+fn decode_position(bytes: &[u8; 22]) -> io::Result<GameState> {
+    let mut rows = vec![String::with_capacity(9); 9];
+
+    for cell in 0..81 {
+        rows[cell / 9].push(match (bytes[cell / 4] >> (2 * (cell % 4))) & 3 {
+            0 => '.',
+            1 => 'O',
+            2 => 'X',
+            _ => return Err(invalid_data(format!("invalid square in cell {}", cell))),
+        });
+    }
+
+    let sub_board = match bytes[21] & 15 {
+        9 => "-".to_string(),
+        index => index.to_string(),
+    };
+
+    from_notation(&format!("{} {} {}", rows.join("/"), bytes[21] >> 4, sub_board)).map_err(invalid_data)
+}
+
+/// Builds a book by searching every position in the first `plies` plies that
+/// can be reached by following book moves. Only the `width` most visited moves
+/// of each search, and only those visited at least `min_share` times as often
+/// as the best one, make it into the book.
+pub struct BookBuilder {
+    plies: usize,
+    time_limit: u128,
+    width: usize,
+    min_share: f32,
+}
+
+impl BookBuilder {
+    pub fn new(plies: usize, time_limit: u128) -> BookBuilder {
+        BookBuilder { plies, time_limit, width: 3, min_share: 0.5 }
+    }
+
+    pub fn with_width(mut self, width: usize) -> BookBuilder {
+        self.width = width;
+        self
+    }
+
+    pub fn with_min_share(mut self, min_share: f32) -> BookBuilder {
+        self.min_share = min_share;
+        self
+    }
+
+    /// Builds the book, calling `progress` with the number of positions in it
+    /// and the ply being searched after every position.
+    pub fn build(&self, evaluator: Option<&dyn Evaluator>, progress: &mut dyn FnMut(usize, usize)) -> OpeningBook {
+        let mut book = OpeningBook::new();
+        let mut seen = HashSet::new();
+        let mut frontier = vec![GameState::new()];
+
+        for ply in 0..self.plies {
+            let mut next_frontier = Vec::new();
+
+            for game_state in frontier {
+                let (canonical, _) = canonicalize(&game_state);
+                if !seen.insert(to_notation(&canonical)) {
+                    continue;
+                }
+
+                let (root, _) = search(&canonical, Limit::Time(self.time_limit), evaluator);
+                let mut moves: Vec<(Action, u32)> = root.visit_distribution().into_iter()
+                    .map(|(action, share)| (action, (share * root.visits() as f32).round() as u32))
+                    .collect();
+                moves.sort_by(|(_, a), (_, b)| b.cmp(a));
+
+                let best = match moves.first() {
+                    Some(&(_, best)) if best > 0 => best as f32,
+                    _ => continue,
+                };
+                moves.retain(|(_, visits)| *visits as f32 >= self.min_share * best);
+                moves.truncate(self.width.max(1));
+
+                for (action, _) in moves.iter() {
+                    let mut child = canonical.clone();
+                    if action.apply(&mut child).is_none() {
+                        next_frontier.push(child);
+                    }
+                }
+
+                book.insert(&canonical, moves);
+                progress(book.len(), ply);
+            }
+
+            frontier = next_frontier;
+        }
+
+        book
+    }
+}
+
+/// Plays book moves, picked at random in proportion to their weights, and asks
+/// the wrapped actor for a move once the game has left the book.
+pub struct BookActor {
+    book: OpeningBook,
+    fallback: Box<dyn Actor>,
+    /// Whether the last move came from the book rather than the fallback.
+    in_book: bool,
+}
+
+impl BookActor {
+    pub fn new(book: OpeningBook, fallback: Box<dyn Actor>) -> BookActor {
+        BookActor { book, fallback, in_book: false }
+    }
+
+    /// A book move for the position, picked at random by weight.
+    fn book_move(&self, game_state: &GameState) -> Option<Action> {
+        let moves = self.book.lookup(game_state)?;
+        let distribution = WeightedIndex::new(moves.iter().map(|(_, weight)| *weight)).ok()?;
+
+        Some(moves[distribution.sample(&mut rand::thread_rng())].0.clone())
+    }
+}
+
+impl Actor for BookActor {
+    fn get_decision(&mut self, game_state: &GameState, context: &Context) -> Result<Decision, ActorError> {
+        self.in_book = false;
+        if let Some(action) = self.book_move(game_state) {
+            self.in_book = true;
+            return Ok(Decision::Move(action));
+        }
+
+        self.fallback.get_decision(game_state, context)
+    }
+
+    fn search_report(&self) -> Option<SearchReport> {
+        if self.in_book { None } else { self.fallback.search_report() }
+    }
+
+    /// Declines draws while the game is still in the book.
+    fn accept_draw(&mut self, game_state: &GameState, context: &Context) -> Result<bool, ActorError> {
+        if self.book.lookup(game_state).is_some() {
+            return Ok(false);
+        }
+
+        self.fallback.accept_draw(game_state, context)
+    }
+
+    fn get_policy(&mut self, game_state: &GameState, context: &Context) -> Result<Vec<(Action, f32)>, ActorError> {
+        if let Some(moves) = self.book.lookup(game_state) {
+            let total: u32 = moves.iter().map(|(_, weight)| weight).sum();
+
+            if total > 0 {
+                return Ok(moves.into_iter()
+                    .map(|(action, weight)| (action, weight as f32 / total as f32))
+                    .collect());
+            }
+        }
+
+        self.fallback.get_policy(game_state, context)
+    }
+
+    fn new_game(&mut self, game_state: &GameState, player: Player) {
+        self.fallback.new_game(game_state, player)
+    }
+
+    fn opponent_moved(&mut self, action: &Action, game_state: &GameState) {
+        self.fallback.opponent_moved(action, game_state)
+    }
+
+    fn game_over(&mut self, record: &GameRecord) {
+        self.fallback.game_over(record)
+    }
+}
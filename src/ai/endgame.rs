@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+
+use crate::game::action::Action;
+use crate::game::board::Owned;
+use crate::game::game_result::GameResult;
+use crate::game::game_state::GameState;
+
+#[derive(Copy, Clone)]
+enum Bound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+/// Exact alpha-beta search over the rest of the game. Values are from the
+/// point of view of the player to move: 1 for a win, 0 for a draw and -1 for a
+/// loss.
+pub struct EndgameSolver {
+    table: HashMap<GameState, (i8, Bound)>,
+    nodes: usize,
+}
+
+impl EndgameSolver {
+    pub fn new() -> EndgameSolver {
+        EndgameSolver { table: HashMap::new(), nodes: 0 }
+    }
+
+    pub fn nodes(&self) -> usize {
+        self.nodes
+    }
+
+    /// Returns a best move and the result it forces with perfect play, or
+    /// `None` if there is no legal move left.
+    pub fn solve(&mut self, game_state: &GameState) -> Option<(Action, GameResult)> {
+        self.solve_until(game_state, &|| false)
+    }
+
+    /// Solves like `solve`, but gives up and returns `None` as soon as `stop`
+    /// says so. `stop` is asked every few hundred positions.
+    pub fn solve_until(&mut self, game_state: &GameState, stop: &dyn Fn() -> bool) -> Option<(Action, GameResult)> {
+        let mut best: Option<(Action, i8)> = None;
+        let mut alpha = -1;
+
+        for action in ordered_actions(game_state) {
+            let mut new_game_state = game_state.clone();
+            let value = match action.apply(&mut new_game_state) {
+                Some(result) => terminal_value(result, game_state),
+                None => -self.negamax(&new_game_state, -1, -alpha, stop)?,
+            };
+
+            if best.as_ref().map_or(true, |(_, best_value)| value > *best_value) {
+                best = Some((action, value));
+                alpha = alpha.max(value);
+            }
+
+            if value == 1 {
+                break;
+            }
+        }
+
+        let (action, value) = best?;
+        let player = game_state.current_player();
+        let result = match value {
+            1 => player.wins(),
+            -1 => player.next().wins(),
+            _ => GameResult::Draw,
+        };
+
+        Some((action, result))
+    }
+
+    fn negamax(&mut self, game_state: &GameState, mut alpha: i8, mut beta: i8, stop: &dyn Fn() -> bool) -> Option<i8> {
+        self.nodes += 1;
+        if self.nodes % 256 == 0 && stop() {
+            return None;
+        }
+
+        if let Some(&(value, bound)) = self.table.get(game_state) {
+            match bound {
+                Bound::Exact => return Some(value),
+                Bound::Lower => alpha = alpha.max(value),
+                Bound::Upper => beta = beta.min(value),
+            }
+
+            if alpha >= beta {
+                return Some(value);
+            }
+        }
+
+        let original_alpha = alpha;
+        let mut best = -1;
+
+        for action in ordered_actions(game_state) {
+            let mut new_game_state = game_state.clone();
+            let value = match action.apply(&mut new_game_state) {
+                Some(result) => terminal_value(result, game_state),
+                None => -self.negamax(&new_game_state, -beta, -alpha, stop)?,
+            };
+
+            best = best.max(value);
+            alpha = alpha.max(value);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        let bound = if best <= original_alpha {
+            Bound::Upper
+        } else if best >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        self.table.insert(game_state.clone(), (best, bound));
+
+        Some(best)
+    }
+}
+
+impl Default for EndgameSolver {
+    fn default() -> EndgameSolver {
+        EndgameSolver::new()
+    }
+}
+
+fn terminal_value(result: GameResult, game_state: &GameState) -> i8 {
+    if result == GameResult::Draw {
+        0
+    } else if result == game_state.current_player().wins() {
+        1
+    } else {
+        -1
+    }
+}
+
+/// Tries moves that close a sub-board first, as they are the most likely to
+/// decide the game.
+fn ordered_actions(game_state: &GameState) -> Vec<Action> {
+    let mut actions = game_state.possible_actions();
+    let own = Some(game_state.current_player().wins());
+
+    actions.sort_by_key(|action| {
+        let mut sub_board = *game_state.board().get(action.sub_x, action.sub_y);
+        sub_board.structure_mut().set_result_at(action.x, action.y, own);
+        sub_board.result().is_some() || sub_board.structure().check_result(action.x, action.y, game_state.rules()).is_none()
+    });
+
+    actions
+}
+
+/// The number of ways the rest of the game can still be played, counting no
+/// further than one past `limit`.
+pub fn continuations(game_state: &GameState, limit: usize) -> usize {
+    let mut count = 0;
+    count_continuations(game_state, limit, &mut count);
+    count
+}
+
+fn count_continuations(game_state: &GameState, limit: usize, count: &mut usize) {
+    for action in game_state.possible_actions() {
+        if *count > limit {
+            return;
+        }
+
+        let mut new_game_state = game_state.clone();
+        match action.apply(&mut new_game_state) {
+            Some(_) => *count += 1,
+            None => count_continuations(&new_game_state, limit, count),
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use crate::game::board::{self, BoardStructure, Owned, LINES};
+use crate::game::game_result::GameResult;
+use crate::game::game_state::GameState;
+use crate::game::player::Player;
+use crate::game::rules::WinCondition;
+
+/// Scores a position from the perspective of the player to move, on the same
+/// scale as `GameResult::score`: 0 is a certain loss, 0.5 a draw and 1 a
+/// certain win. Evaluators are shared between search threads.
+pub trait Evaluator: Sync {
+    fn evaluate(&self, game_state: &GameState) -> f32;
+}
+
+/// Hand-written position knowledge: won sub-boards, open lines one short of
+/// completion (two-in-a-rows on the standard board) on both the macro board and
+/// the sub-boards, central squares and the freedom to play anywhere.
+pub struct HeuristicEvaluator {
+    pub sub_board_won: f32,
+    pub macro_two_in_line: f32,
+    pub center_sub_board: f32,
+    pub micro_two_in_line: f32,
+    pub center_square: f32,
+    pub free_move: f32,
+    pub scale: f32,
+}
+
+impl HeuristicEvaluator {
+    pub fn new() -> HeuristicEvaluator {
+        HeuristicEvaluator {
+            sub_board_won: 5.,
+            macro_two_in_line: 4.,
+            center_sub_board: 3.,
+            micro_two_in_line: 0.5,
+            center_square: 0.3,
+            free_move: 1.,
+            scale: 10.,
+        }
+    }
+
+    /// Raw score for `player`; positive values favour `player`.
+    pub fn raw_score(&self, game_state: &GameState, player: Player) -> f32 {
+        let board = game_state.board();
+        let size = board.size();
+        let win_length = game_state.rules().win_length;
+        let mut score = 0.;
+
+        for sub_y in 0..size {
+            for sub_x in 0..size {
+                let sub_board = board.get(sub_x, sub_y);
+                let sub_weight = if is_center(sub_x, sub_y, size) { self.center_sub_board } else { 1. };
+
+                match sub_board.result() {
+                    Some(GameResult::Draw) => {},
+                    Some(result) => score += sign(result, player) * self.sub_board_won * sub_weight,
+                    None => {
+                        score += self.micro_two_in_line * lines_score(sub_board.structure(), player, win_length);
+
+                        for y in 0..size {
+                            for x in 0..size {
+                                if is_center(x, y, size) {
+                                    score += self.center_square * sub_weight *
+                                        sub_board.get(x, y).result().map_or(0., |result| sign(result, player));
+                                }
+                            }
+                        }
+                    },
+                }
+            }
+        }
+
+        let macro_lines = match game_state.rules().win_condition {
+            WinCondition::Standard => 1.,
+            WinCondition::Misere => -1.,
+            WinCondition::MostSubBoards => 0.,
+        };
+        score += macro_lines * self.macro_two_in_line * lines_score(board.structure(), player, win_length);
+
+        if game_state.current_sub_x.is_none() {
+            score += if game_state.current_player() == player { self.free_move } else { -self.free_move };
+        }
+
+        score
+    }
+}
+
+impl Default for HeuristicEvaluator {
+    fn default() -> HeuristicEvaluator {
+        HeuristicEvaluator::new()
+    }
+}
+
+impl Evaluator for HeuristicEvaluator {
+    fn evaluate(&self, game_state: &GameState) -> f32 {
+        let score = self.raw_score(game_state, game_state.current_player());
+        1. / (1. + (-score / self.scale).exp())
+    }
+}
+
+fn sign(result: GameResult, player: Player) -> f32 {
+    if result == GameResult::Draw {
+        0.
+    } else if result == player.wins() {
+        1.
+    } else {
+        -1.
+    }
+}
+
+/// Whether a square is the centre of the board, or one of the four central
+/// squares on an even-sized board.
+fn is_center(x: usize, y: usize, size: usize) -> bool {
+    let central = |coordinate: usize| 2 * coordinate + 1 >= size - 1 && 2 * coordinate < size + 1;
+    central(x) && central(y)
+}
+
+/// Counts the lines that one player could still complete and is one square
+/// short of, minus the opponent's.
+fn lines_score<T: Owned>(structure: &BoardStructure<T>, player: Player, win_length: usize) -> f32 {
+    if structure.size() == 3 && win_length == 3 {
+        LINES.iter().map(|line| line_score(structure, player, line)).sum()
+    } else {
+        board::lines(structure.size(), win_length).iter().map(|line| line_score(structure, player, line)).sum()
+    }
+}
+
+fn line_score<T: Owned>(structure: &BoardStructure<T>, player: Player, line: &[(usize, usize)]) -> f32 {
+    let mut own = 0;
+    let mut other = 0;
+
+    for &(x, y) in line.iter() {
+        match structure.get(x, y).result() {
+            None => {},
+            Some(GameResult::Draw) => return 0.,
+            Some(result) if result == player.wins() => own += 1,
+            Some(_) => other += 1,
+        }
+    }
+
+    if own > 0 && own + 1 == line.len() && other == 0 {
+        1.
+    } else if other > 0 && other + 1 == line.len() && own == 0 {
+        -1.
+    } else {
+        0.
+    }
+}
@@ -1,10 +1,16 @@
+use std::thread;
 use std::time::SystemTime;
 
-use crate::actor::Actor;
+use crate::actor::{Actor, ActorError, Context, Decision, SearchReport};
+use crate::ai::analysis::MoveAnalysis;
+use crate::ai::endgame::{continuations, EndgameSolver};
+use crate::ai::evaluator::Evaluator;
 use crate::game::action::Action;
 use crate::game::board::Owned;
 use crate::game::game_result::GameResult;
 use crate::game::game_state::GameState;
+use crate::game::player::Player;
+use crate::game::record::GameRecord;
 
 use crate::util::non_nan::NonNan;
 
@@ -66,6 +72,25 @@ impl Node {
         )
     }
 
+    pub fn visits(&self) -> usize {
+        self.visits
+    }
+
+    /// The share of the visits that went to each move.
+    pub fn visit_distribution(&self) -> Vec<(Action, f32)> {
+        let total = self.children().iter().map(|node| node.visits).sum::<usize>().max(1) as f32;
+
+        self.children().iter().map(|node| {
+            (action_between(self, node), node.visits as f32 / total)
+        }).collect()
+    }
+
+    /// For each move: the simulations that went to it and the average score
+    /// for the player making it.
+    pub fn move_statistics(&self) -> Vec<(Action, usize, f32)> {
+        self.children().iter().map(|node| (action_between(self, node), node.visits, node.weight())).collect()
+    }
+
     pub fn weight(&self) -> f32 {
         if self.visits == 0 { 0. } else { self.value / self.visits as f32 }
     }
@@ -93,17 +118,52 @@ impl Node {
         self.children.len() - self.children_left as usize - 1
     }
 
-    pub fn update(&mut self, result: GameResult) {
+    /// The subtree after `action`, to search on from once it is played.
+    pub fn into_child(self, action: &Action) -> Option<Node> {
+        let coordinates = (action.sub_x, action.sub_y, action.x, action.y);
+        let index = self.children.iter().position(|child| {
+            let between = action_between(&self, child);
+            (between.sub_x, between.sub_y, between.x, between.y) == coordinates
+        })?;
+        self.children.into_iter().nth(index)
+    }
+
+    /// Adds the statistics of a search of the same position to this one's,
+    /// all the way down, so that every node counts the simulations through it
+    /// in either search. Children are expanded in the same order in every
+    /// search, so they line up.
+    fn merge(&mut self, other: Node) {
+        self.visits += other.visits;
+        self.value += other.value;
+
+        if other.children_left == -1 {
+            return;
+        }
+        if self.children_left == -1 {
+            self.children = other.children;
+            self.children_left = other.children_left;
+            return;
+        }
+
+        self.children_left = self.children_left.min(other.children_left);
+        for (child, other_child) in self.children.iter_mut().zip(other.children) {
+            child.merge(other_child);
+        }
+    }
+
+    pub fn update(&mut self, score: f32) {
         self.visits += 1;
-        self.value += result.score(self.state.current_player.next());
+        self.value += if self.state.current_player.next() == Player::Player1 { score } else { 1. - score };
     }
 }
 
 fn action_between(node1: &Node, node2: &Node) -> Action {
-    for sub_x in 0..3 {
-        for sub_y in 0..3 {
-            for x in 0..3 {
-                for y in 0..3 {
+    let size = node1.state().board().size();
+
+    for sub_x in 0..size {
+        for sub_y in 0..size {
+            for x in 0..size {
+                for y in 0..size {
                     if node1.state().board().get(sub_x, sub_y).get(x, y).result() !=
                         node2.state().board().get(sub_x, sub_y).get(x, y).result() {
 
@@ -130,68 +190,299 @@ fn initial_vec(game_state: &GameState) -> Vec<Node> {
         }).collect()
 }
 
-pub fn mcts_rec(root: &mut Node) -> GameResult {
+fn evaluate_leaf(game_state: &GameState, evaluator: Option<&dyn Evaluator>) -> f32 {
+    match evaluator {
+        Some(evaluator) => {
+            let score = evaluator.evaluate(game_state);
+            if game_state.current_player() == Player::Player1 { score } else { 1. - score }
+        },
+        None => game_state.play_randomly().score(Player::Player1),
+    }
+}
+
+pub fn mcts_rec(root: &mut Node, evaluator: Option<&dyn Evaluator>) -> f32 {
     if root.fully_expanded() {
         let index = root.best_child(root.visits);
         let best_child = root.children_mut().get_mut(index).unwrap();
 
-        let result = match best_child.result {
-            Some(game_result) => game_result,
-            None => mcts_rec(best_child),
+        let score = match best_child.result {
+            Some(game_result) => game_result.score(Player::Player1),
+            None => mcts_rec(best_child, evaluator),
         };
 
-        best_child.update(result);
+        best_child.update(score);
 
-        result
+        score
     } else {
         let index = root.expand();
         let new_child = root.children_mut().get_mut(index).unwrap();
 
-        let result = match new_child.result {
-            Some(game_result) => game_result,
-            None => root.state_mut().play_randomly(),
+        let score = match new_child.result {
+            Some(game_result) => game_result.score(Player::Player1),
+            None => evaluate_leaf(new_child.state(), evaluator),
         };
 
-        let new_child = root.children_mut().get_mut(index).unwrap();
-        new_child.update(result);
+        new_child.update(score);
 
-        result
+        score
     }
 }
 
-pub fn mcts(game_state: &mut GameState, time: u128) -> Action {
-    let mut root = Node::new(
-        game_state.clone(),
-        None,
-    );
+/// When a search stops.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Limit {
+    /// After this many milliseconds.
+    Time(u128),
+    /// After this many simulations.
+    Iterations(usize),
+}
+
+impl Limit {
+    pub fn reached(self, start_time: SystemTime, count: usize) -> bool {
+        match self {
+            Limit::Time(time) => SystemTime::now().duration_since(start_time).unwrap().as_millis() >= time,
+            Limit::Iterations(iterations) => count >= iterations,
+        }
+    }
+
+    /// What is left of this limit after searching since `start_time` without
+    /// simulating.
+    fn left(self, start_time: SystemTime) -> Limit {
+        match self {
+            Limit::Time(time) => Limit::Time(time.saturating_sub(SystemTime::now().duration_since(start_time).unwrap().as_millis())),
+            Limit::Iterations(iterations) => Limit::Iterations(iterations),
+        }
+    }
+
+    /// The share of this limit one of `threads` parallel searches gets.
+    fn per_thread(self, threads: usize) -> Limit {
+        match self {
+            Limit::Time(time) => Limit::Time(time),
+            Limit::Iterations(iterations) => Limit::Iterations(iterations.div_ceil(threads)),
+        }
+    }
+}
 
+pub fn search(game_state: &GameState, limit: Limit, evaluator: Option<&dyn Evaluator>) -> (Node, usize) {
+    search_until(game_state, limit, evaluator, &|| false)
+}
+
+/// Searches like `search`, but also stops as soon as `stop` says so, after at
+/// least one simulation.
+pub fn search_until(game_state: &GameState, limit: Limit, evaluator: Option<&dyn Evaluator>, stop: &(dyn Fn() -> bool + Sync)) -> (Node, usize) {
+    search_from(Node::new(game_state.clone(), None), limit, evaluator, stop)
+}
+
+/// Searches on from the statistics already in `root`, as `search_until` does.
+/// The count is of the new simulations only.
+pub fn search_from(mut root: Node, limit: Limit, evaluator: Option<&dyn Evaluator>, stop: &(dyn Fn() -> bool + Sync)) -> (Node, usize) {
     let start_time = SystemTime::now();
     let mut count: usize = 0;
-    while SystemTime::now().duration_since(start_time).unwrap().as_millis() < time {
-        let result = mcts_rec(&mut root);
-        root.update(result);
+    while count == 0 || !(limit.reached(start_time, count) || stop()) {
+        let score = mcts_rec(&mut root, evaluator);
+        root.update(score);
         count += 1;
     }
 
-    println!("Number of simulations: {}", count);
+    (root, count)
+}
+
+/// Runs independent searches on `threads` threads and adds up their trees.
+pub fn search_parallel(game_state: &GameState, limit: Limit, threads: usize, evaluator: Option<&dyn Evaluator>) -> (Node, usize) {
+    search_parallel_until(game_state, limit, threads, evaluator, &|| false)
+}
+
+/// `search_parallel` with a `stop` condition for every thread, as in
+/// `search_until`.
+pub fn search_parallel_until(game_state: &GameState, limit: Limit, threads: usize, evaluator: Option<&dyn Evaluator>, stop: &(dyn Fn() -> bool + Sync)) -> (Node, usize) {
+    search_parallel_from(Node::new(game_state.clone(), None), limit, threads, evaluator, stop)
+}
+
+/// `search_parallel_until` on from `root`. One thread searches on from it,
+/// the others start afresh.
+pub fn search_parallel_from(root: Node, limit: Limit, threads: usize, evaluator: Option<&dyn Evaluator>, stop: &(dyn Fn() -> bool + Sync)) -> (Node, usize) {
+    if threads <= 1 {
+        return search_from(root, limit, evaluator, stop);
+    }
+
+    let limit = limit.per_thread(threads);
+    let game_state = root.state().clone();
+    let mut roots = vec![root];
+    roots.extend((1..threads).map(|_| Node::new(game_state.clone(), None)));
+    let results: Vec<(Node, usize)> = thread::scope(|scope| {
+        let handles: Vec<_> = roots.into_iter()
+            .map(|root| scope.spawn(move || search_from(root, limit, evaluator, stop)))
+            .collect();
+
+        handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+    });
+
+    let mut results = results.into_iter();
+    let (mut root, mut count) = results.next().unwrap();
+    for (other, other_count) in results {
+        root.merge(other);
+        count += other_count;
+    }
+
+    (root, count)
+}
+
+pub fn mcts(game_state: &GameState, limit: Limit, threads: usize, evaluator: Option<&dyn Evaluator>) -> (Action, SearchReport) {
+    mcts_until(game_state, limit, threads, evaluator, &|| false)
+}
+
+/// `mcts` with a `stop` condition, as in `search_until`.
+pub fn mcts_until(game_state: &GameState, limit: Limit, threads: usize, evaluator: Option<&dyn Evaluator>, stop: &(dyn Fn() -> bool + Sync)) -> (Action, SearchReport) {
+    let (root, count) = search_parallel_until(game_state, limit, threads, evaluator, stop);
     let (best_action, weight) = root.best_action();
-    println!("Expected result: {}", weight);
 
-    best_action
+    (best_action, SearchReport { simulations: count, expected: weight, proven: None })
 }
 
 pub struct MCTSActor {
-    time_limit: u128,
+    limit: Limit,
+    threads: usize,
+    evaluator: Option<Box<dyn Evaluator>>,
+    endgame_threshold: usize,
+    report: Option<SearchReport>,
+    /// What the last search found beyond the move it chose, kept for the next
+    /// move as long as the game follows it.
+    tree: Option<Node>,
 }
 
 impl MCTSActor {
     pub fn new(time_limit: u128) -> MCTSActor {
-        MCTSActor { time_limit }
+        MCTSActor { limit: Limit::Time(time_limit), threads: 1, evaluator: None, endgame_threshold: 10000, report: None, tree: None }
+    }
+
+    /// Searches a fixed number of simulations per move instead of for a fixed
+    /// time.
+    pub fn with_iterations(mut self, iterations: usize) -> MCTSActor {
+        self.limit = Limit::Iterations(iterations);
+        self
+    }
+
+    /// Splits the search over this many threads. Each searches its own tree and
+    /// the trees are added up once they are done.
+    pub fn with_threads(mut self, threads: usize) -> MCTSActor {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Solves the game exactly instead of searching once it can be played out
+    /// in at most this many ways. 0 turns the solver off.
+    pub fn with_endgame_threshold(mut self, endgame_threshold: usize) -> MCTSActor {
+        self.endgame_threshold = endgame_threshold;
+        self
+    }
+
+    /// Solves the position if it is small enough and the solver finishes
+    /// within the search's time limit, started at `start_time`, and before
+    /// `stop` says so.
+    fn solve_endgame(&self, game_state: &GameState, start_time: SystemTime, stop: &dyn Fn() -> bool) -> Option<(Action, GameResult)> {
+        if self.endgame_threshold == 0 || continuations(game_state, self.endgame_threshold) > self.endgame_threshold {
+            return None;
+        }
+
+        EndgameSolver::new().solve_until(game_state, &|| stop() || self.limit.reached(start_time, 0))
+    }
+
+    /// Replaces the random playouts at the leaves of the search tree with the
+    /// given evaluator.
+    pub fn with_evaluator(mut self, evaluator: Box<dyn Evaluator>) -> MCTSActor {
+        self.evaluator = Some(evaluator);
+        self
+    }
+}
+
+impl MCTSActor {
+    /// The moves the search considered, most simulated first, with the share
+    /// of the simulations each got and the expected score it gives the player
+    /// to move.
+    pub fn analyse(&self, game_state: &GameState) -> Vec<MoveAnalysis> {
+        let player = game_state.current_player();
+        let start_time = SystemTime::now();
+        if let Some((action, result)) = self.solve_endgame(game_state, start_time, &|| false) {
+            return vec![MoveAnalysis { action, share: 1., score: result.score(player) }];
+        }
+
+        let (root, _) = search_parallel(game_state, self.limit.left(start_time), self.threads, self.evaluator.as_deref());
+        let statistics = root.move_statistics();
+        let total = statistics.iter().map(|(_, visits, _)| visits).sum::<usize>().max(1) as f32;
+
+        let mut analysis: Vec<MoveAnalysis> = statistics.into_iter()
+            .map(|(action, visits, score)| MoveAnalysis { action, share: visits as f32 / total, score })
+            .collect();
+        analysis.sort_by(|a, b| b.share.partial_cmp(&a.share).unwrap());
+        analysis
+    }
+
+    /// Picks a move, searching on from the tree kept from earlier moves when
+    /// it reaches this position.
+    fn choose(&mut self, game_state: &GameState, stop: &(dyn Fn() -> bool + Sync)) -> Action {
+        let tree = self.tree.take().filter(|tree| tree.state() == game_state);
+        let start_time = SystemTime::now();
+
+        let (action, report) = match self.solve_endgame(game_state, start_time, stop) {
+            Some((action, result)) => {
+                let expected = result.score(game_state.current_player());
+                (action, SearchReport { simulations: 0, expected, proven: Some(result) })
+            },
+            None => {
+                let root = tree.unwrap_or_else(|| Node::new(game_state.clone(), None));
+                let (root, count) = search_parallel_from(root, self.limit.left(start_time), self.threads, self.evaluator.as_deref(), stop);
+                let (action, expected) = root.best_action();
+
+                self.tree = root.into_child(&action);
+                (action, SearchReport { simulations: count, expected, proven: None })
+            },
+        };
+
+        self.report = Some(report);
+        action
     }
 }
 
 impl Actor for MCTSActor {
-    fn get_action(&self, game_state: &mut GameState) -> Action {
-        mcts(game_state, self.time_limit)
+    fn new_game(&mut self, _game_state: &GameState, _player: Player) {
+        self.tree = None;
+    }
+
+    /// Searches until its own limit, the clock or cancellation stops it. When
+    /// the clock runs out the best move so far is played.
+    fn get_decision(&mut self, game_state: &GameState, context: &Context) -> Result<Decision, ActorError> {
+        let action = self.choose(game_state, &|| context.should_stop());
+        if context.cancel.is_cancelled() {
+            return Err(ActorError::Cancelled);
+        }
+
+        Ok(Decision::Move(action))
+    }
+
+    fn search_report(&self) -> Option<SearchReport> {
+        self.report.clone()
+    }
+
+    fn get_policy(&mut self, game_state: &GameState, context: &Context) -> Result<Vec<(Action, f32)>, ActorError> {
+        let stop = || context.should_stop();
+        let start_time = SystemTime::now();
+        if let Some((action, _)) = self.solve_endgame(game_state, start_time, &stop) {
+            return Ok(vec![(action, 1.)]);
+        }
+
+        let (root, _) = search_parallel_until(game_state, self.limit.left(start_time), self.threads, self.evaluator.as_deref(), &stop);
+        if context.cancel.is_cancelled() {
+            return Err(ActorError::Cancelled);
+        }
+
+        Ok(root.visit_distribution())
+    }
+
+    fn opponent_moved(&mut self, action: &Action, _game_state: &GameState) {
+        self.tree = self.tree.take().and_then(|tree| tree.into_child(action));
+    }
+
+    fn game_over(&mut self, _record: &GameRecord) {
+        self.tree = None;
     }
 }
@@ -0,0 +1,289 @@
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use rand::Rng;
+
+use crate::ai::evaluator::Evaluator;
+use crate::game::action::Action;
+use crate::game::board::Owned;
+use crate::game::game_state::GameState;
+
+pub const CELLS: usize = 81;
+pub const INPUT_PLANES: usize = 4;
+pub const INPUT_SIZE: usize = INPUT_PLANES * CELLS;
+
+const MAGIC: &[u8; 4] = b"UTNN";
+const VERSION: u32 = 1;
+
+/// Bounds on the hidden layers a weights file may declare, so that a corrupt
+/// header cannot make `Network::read` allocate without limit.
+const MAX_LAYERS: usize = 16;
+const MAX_LAYER_SIZE: usize = 4096;
+
+/// Index of the cell an action plays in, counting row by row over the full 9x9
+/// grid of the standard board.
+pub fn cell_index(action: &Action) -> usize {
+    27 * action.sub_y + 9 * action.y + 3 * action.sub_x + action.x
+}
+
+pub fn cell_action(index: usize, full_board: bool) -> Action {
+    let (row, column) = (index / 9, index % 9);
+    Action::new(column / 3, row / 3, column % 3, row % 3, full_board)
+}
+
+/// Encodes a position as input planes from the perspective of the player to
+/// move: their squares, the opponent's squares, the legal moves and the squares
+/// of closed sub-boards.
+pub fn encode(game_state: &GameState) -> Vec<f32> {
+    assert!(game_state.rules().is_standard_size(), "networks only play on the standard board");
+
+    let mut input = vec![0.; INPUT_SIZE];
+    let own = game_state.current_player().wins();
+
+    for sub_y in 0..3 {
+        for sub_x in 0..3 {
+            let sub_board = game_state.board().get(sub_x, sub_y);
+
+            for y in 0..3 {
+                for x in 0..3 {
+                    let index = cell_index(&Action::new(sub_x, sub_y, x, y, false));
+
+                    match sub_board.get(x, y).result() {
+                        Some(result) if result == own => input[index] = 1.,
+                        Some(_) => input[CELLS + index] = 1.,
+                        None => {},
+                    }
+
+                    if sub_board.result().is_some() {
+                        input[3 * CELLS + index] = 1.;
+                    }
+                }
+            }
+        }
+    }
+
+    for action in game_state.possible_actions() {
+        input[2 * CELLS + cell_index(&action)] = 1.;
+    }
+
+    input
+}
+
+#[derive(Clone)]
+pub struct Layer {
+    pub inputs: usize,
+    pub outputs: usize,
+    pub weights: Vec<f32>,
+    pub biases: Vec<f32>,
+}
+
+impl Layer {
+    pub fn new(inputs: usize, outputs: usize) -> Layer {
+        Layer {
+            inputs,
+            outputs,
+            weights: vec![0.; inputs * outputs],
+            biases: vec![0.; outputs],
+        }
+    }
+
+    pub fn random<R: Rng>(inputs: usize, outputs: usize, rng: &mut R) -> Layer {
+        let bound = (6. / (inputs + outputs) as f32).sqrt();
+        let mut layer = Layer::new(inputs, outputs);
+
+        for weight in layer.weights.iter_mut() {
+            *weight = rng.gen_range(-bound, bound);
+        }
+
+        layer
+    }
+
+    pub fn forward(&self, input: &[f32]) -> Vec<f32> {
+        (0..self.outputs).map(|o| {
+            let row = &self.weights[o * self.inputs..(o + 1) * self.inputs];
+            row.iter().zip(input).fold(self.biases[o], |sum, (w, i)| sum + w * i)
+        }).collect()
+    }
+}
+
+/// A small fully connected network with a ReLU trunk, a policy head giving one
+/// logit per cell and a tanh value head, both from the point of view of the
+/// player to move.
+#[derive(Clone)]
+pub struct Network {
+    pub trunk: Vec<Layer>,
+    pub policy: Layer,
+    pub value: Layer,
+}
+
+impl Network {
+    pub fn random<R: Rng>(hidden: &[usize], rng: &mut R) -> Network {
+        let mut trunk = Vec::with_capacity(hidden.len());
+        let mut inputs = INPUT_SIZE;
+
+        for &outputs in hidden {
+            trunk.push(Layer::random(inputs, outputs, rng));
+            inputs = outputs;
+        }
+
+        Network {
+            trunk,
+            policy: Layer::random(inputs, CELLS, rng),
+            value: Layer::random(inputs, 1, rng),
+        }
+    }
+
+    pub fn hidden_sizes(&self) -> Vec<usize> {
+        self.trunk.iter().map(|layer| layer.outputs).collect()
+    }
+
+    /// Returns the policy logits over all 81 cells and the value in [-1, 1].
+    pub fn forward(&self, input: &[f32]) -> (Vec<f32>, f32) {
+        let mut activation = input.to_vec();
+
+        for layer in self.trunk.iter() {
+            activation = layer.forward(&activation);
+            for a in activation.iter_mut() {
+                *a = a.max(0.);
+            }
+        }
+
+        let logits = self.policy.forward(&activation);
+        let value = self.value.forward(&activation)[0].tanh();
+
+        (logits, value)
+    }
+
+    /// Priors over the legal moves, normalised with a softmax, and the value of
+    /// the position for the player to move.
+    pub fn predict(&self, game_state: &GameState) -> (Vec<(Action, f32)>, f32) {
+        let (logits, value) = self.forward(&encode(game_state));
+        let actions = game_state.possible_actions();
+
+        let max = actions.iter()
+            .map(|action| logits[cell_index(action)])
+            .fold(f32::NEG_INFINITY, f32::max);
+        let exps: Vec<f32> = actions.iter()
+            .map(|action| (logits[cell_index(action)] - max).exp())
+            .collect();
+        let total: f32 = exps.iter().sum();
+
+        let priors = actions.into_iter()
+            .zip(exps)
+            .map(|(action, exp)| (action, exp / total))
+            .collect();
+
+        (priors, value)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Network> {
+        Network::read(&mut BufReader::new(File::open(path)?))
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write(&mut writer)?;
+        writer.flush()
+    }
+
+    /// Reads a network in the weights format: the magic `UTNN`, a format
+    /// version, the number of hidden layers and their sizes, followed by the
+    /// weights and biases of every layer (trunk, policy head, value head), all
+    /// as little-endian 32-bit values.
+    pub fn read<R: Read>(reader: &mut R) -> io::Result<Network> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid_data("not a network weights file"));
+        }
+
+        let version = read_u32(reader)?;
+        if version != VERSION {
+            return Err(invalid_data(&format!("unsupported weights version {}", version)));
+        }
+
+        let layers = read_u32(reader)? as usize;
+        if layers > MAX_LAYERS {
+            return Err(invalid_data(&format!("too many hidden layers: {}", layers)));
+        }
+
+        let mut hidden = Vec::with_capacity(layers);
+        for _ in 0..layers {
+            let size = read_u32(reader)? as usize;
+            if size == 0 || size > MAX_LAYER_SIZE {
+                return Err(invalid_data(&format!("invalid hidden layer size {}", size)));
+            }
+            hidden.push(size);
+        }
+
+        let mut trunk = Vec::with_capacity(layers);
+        let mut inputs = INPUT_SIZE;
+        for &outputs in hidden.iter() {
+            trunk.push(read_layer(reader, inputs, outputs)?);
+            inputs = outputs;
+        }
+
+        Ok(Network {
+            trunk,
+            policy: read_layer(reader, inputs, CELLS)?,
+            value: read_layer(reader, inputs, 1)?,
+        })
+    }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        writer.write_all(&(self.trunk.len() as u32).to_le_bytes())?;
+        for layer in self.trunk.iter() {
+            writer.write_all(&(layer.outputs as u32).to_le_bytes())?;
+        }
+
+        for layer in self.trunk.iter().chain(vec![&self.policy, &self.value]) {
+            for value in layer.weights.iter().chain(layer.biases.iter()) {
+                writer.write_all(&value.to_le_bytes())?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Evaluator for Network {
+    fn evaluate(&self, game_state: &GameState) -> f32 {
+        let (_, value) = self.forward(&encode(game_state));
+        (value + 1.) / 2.
+    }
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_f32<R: Read>(reader: &mut R) -> io::Result<f32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(f32::from_le_bytes(bytes))
+}
+
+fn read_layer<R: Read>(reader: &mut R, inputs: usize, outputs: usize) -> io::Result<Layer> {
+    if inputs.checked_mul(outputs).is_none() {
+        return Err(invalid_data(&format!("layer of {}x{} weights is too large", inputs, outputs)));
+    }
+    let mut layer = Layer::new(inputs, outputs);
+
+    for weight in layer.weights.iter_mut() {
+        *weight = read_f32(reader)?;
+    }
+    for bias in layer.biases.iter_mut() {
+        *bias = read_f32(reader)?;
+    }
+
+    Ok(layer)
+}
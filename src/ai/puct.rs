@@ -0,0 +1,176 @@
+use std::time::SystemTime;
+
+use crate::actor::{Actor, ActorError, Context, Decision, SearchReport};
+use crate::ai::mcts::Limit;
+use crate::ai::nn::Network;
+use crate::game::action::Action;
+use crate::game::game_result::GameResult;
+use crate::game::game_state::GameState;
+use crate::game::player::Player;
+
+use crate::util::non_nan::NonNan;
+
+pub struct PUCTNode {
+    visits: usize,
+    value: f32,
+    prior: f32,
+    action: Option<Action>,
+    children: Vec<PUCTNode>,
+    expanded: bool,
+    state: GameState,
+    result: Option<GameResult>,
+}
+
+impl PUCTNode {
+    pub fn new(state: GameState, action: Option<Action>, prior: f32, result: Option<GameResult>) -> PUCTNode {
+        PUCTNode {
+            visits: 0,
+            value: 0.,
+            prior,
+            action,
+            children: Vec::new(),
+            expanded: false,
+            state,
+            result,
+        }
+    }
+
+    pub fn children(&self) -> &Vec<PUCTNode> {
+        &self.children
+    }
+
+    pub fn state(&self) -> &GameState {
+        &self.state
+    }
+
+    pub fn weight(&self) -> f32 {
+        if self.visits == 0 { 0. } else { self.value / self.visits as f32 }
+    }
+
+    pub fn search_weight(&self, parent_visits_sqrt: f32, exploration: f32) -> f32 {
+        let exploitation = if self.visits == 0 { 0.5 } else { self.weight() };
+        exploitation + exploration * self.prior * parent_visits_sqrt / (1 + self.visits) as f32
+    }
+
+    pub fn best_child(&self, exploration: f32) -> usize {
+        let visits_sqrt = (self.visits as f32).sqrt();
+
+        let (index, _) = self.children().iter().enumerate().max_by_key(|(_, node)| {
+            NonNan::new(node.search_weight(visits_sqrt, exploration)).unwrap()
+        }).unwrap();
+
+        index
+    }
+
+    /// The most visited move, as is usual for PUCT, with its expected result.
+    pub fn best_action(&self) -> (Action, f32) {
+        let node = self.children().iter().max_by_key(|node| node.visits).unwrap();
+
+        (node.action.clone().unwrap(), node.weight())
+    }
+
+    /// Creates all children with their priors and returns the network's value
+    /// for this node, as a score for player 1.
+    pub fn expand(&mut self, network: &Network) -> f32 {
+        let (priors, value) = network.predict(&self.state);
+
+        self.children = priors.into_iter().map(|(action, prior)| {
+            let mut new_game_state = self.state.clone();
+            let result = action.apply(&mut new_game_state);
+            PUCTNode::new(new_game_state, Some(action), prior, result)
+        }).collect();
+        self.expanded = true;
+
+        let score = (value + 1.) / 2.;
+        if self.state.current_player() == Player::Player1 { score } else { 1. - score }
+    }
+
+    pub fn update(&mut self, score: f32) {
+        self.visits += 1;
+        self.value += if self.state.current_player.next() == Player::Player1 { score } else { 1. - score };
+    }
+}
+
+pub fn puct_rec(root: &mut PUCTNode, network: &Network, exploration: f32) -> f32 {
+    if let Some(result) = root.result {
+        return result.score(Player::Player1);
+    }
+
+    if !root.expanded {
+        return root.expand(network);
+    }
+
+    let index = root.best_child(exploration);
+    let best_child = &mut root.children[index];
+    let score = puct_rec(best_child, network, exploration);
+    best_child.update(score);
+
+    score
+}
+
+pub fn puct(game_state: &GameState, limit: Limit, network: &Network, exploration: f32) -> (Action, SearchReport) {
+    puct_until(game_state, limit, network, exploration, &|| false)
+}
+
+/// Searches like `puct`, but also stops as soon as `stop` says so, after at
+/// least two simulations.
+pub fn puct_until(game_state: &GameState, limit: Limit, network: &Network, exploration: f32, stop: &dyn Fn() -> bool) -> (Action, SearchReport) {
+    let mut root = PUCTNode::new(game_state.clone(), None, 1., None);
+
+    let start_time = SystemTime::now();
+    let mut count: usize = 0;
+    while count < 2 || !(limit.reached(start_time, count) || stop()) {
+        let score = puct_rec(&mut root, network, exploration);
+        root.update(score);
+        count += 1;
+    }
+
+    let (best_action, weight) = root.best_action();
+
+    (best_action, SearchReport { simulations: count, expected: weight, proven: None })
+}
+
+/// AlphaZero-style search: the tree is guided by the network's move priors and
+/// leaves are scored by its value head instead of random playouts.
+pub struct PUCTActor {
+    network: Network,
+    limit: Limit,
+    exploration: f32,
+    report: Option<SearchReport>,
+}
+
+impl PUCTActor {
+    pub fn new(network: Network, time_limit: u128) -> PUCTActor {
+        PUCTActor { network, limit: Limit::Time(time_limit), exploration: 1.5, report: None }
+    }
+
+    /// Searches a fixed number of simulations per move instead of for a fixed
+    /// time.
+    pub fn with_iterations(mut self, iterations: usize) -> PUCTActor {
+        self.limit = Limit::Iterations(iterations);
+        self
+    }
+
+    pub fn with_exploration(mut self, exploration: f32) -> PUCTActor {
+        self.exploration = exploration;
+        self
+    }
+}
+
+impl Actor for PUCTActor {
+    /// Searches until its own limit, the clock or cancellation stops it. When
+    /// the clock runs out the best move so far is played.
+    fn get_decision(&mut self, game_state: &GameState, context: &Context) -> Result<Decision, ActorError> {
+        let (action, report) = puct_until(game_state, self.limit, &self.network, self.exploration, &|| context.should_stop());
+        if context.cancel.is_cancelled() {
+            return Err(ActorError::Cancelled);
+        }
+
+        self.report = Some(report);
+        Ok(Decision::Move(action))
+    }
+
+    fn search_report(&self) -> Option<SearchReport> {
+        self.report.clone()
+    }
+}
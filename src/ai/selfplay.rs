@@ -0,0 +1,200 @@
+use std::io::{self, Write};
+
+use rand::distributions::WeightedIndex;
+use rand::prelude::*;
+use rand::rngs::StdRng;
+
+use crate::actor::{Actor, ActorError, Context};
+use crate::ai::nn::{cell_action, cell_index};
+use crate::game::action::Action;
+use crate::game::game_result::GameResult;
+use crate::game::game_state::GameState;
+use crate::game::notation::{from_notation, result_from_notation, result_to_notation, to_notation};
+use crate::game::record::GameRecord;
+
+use crate::util::non_nan::NonNan;
+
+/// A position from a self-play game together with the move distribution the
+/// actor produced for it and the final result of the game.
+pub struct PositionRecord {
+    pub game_state: GameState,
+    pub policy: Vec<(Action, f32)>,
+    pub result: GameResult,
+}
+
+impl PositionRecord {
+    /// One tab-separated line: the position in notation, the result (`1`, `2`
+    /// or `=`) and the policy as space-separated `cell:probability` pairs, with
+    /// cells numbered row by row over the full grid.
+    pub fn to_line(&self) -> String {
+        let policy: Vec<String> = self.policy.iter()
+            .map(|(action, probability)| format!("{}:{}", cell_index(action), probability))
+            .collect();
+
+        format!("{}\t{}\t{}", to_notation(&self.game_state), result_to_notation(self.result), policy.join(" "))
+    }
+
+    pub fn from_line(line: &str) -> Result<PositionRecord, String> {
+        let fields: Vec<&str> = line.trim_end().split('\t').collect();
+        if fields.len() != 3 {
+            return Err(format!("expected 3 fields, found {}", fields.len()));
+        }
+
+        let game_state = from_notation(fields[0])?;
+        let result = result_from_notation(fields[1])?;
+        let full_board = game_state.current_sub_x.is_none();
+
+        let policy = fields[2].split_whitespace().map(|entry| {
+            let mut parts = entry.split(':');
+            let cell = parts.next().and_then(|cell| cell.parse::<usize>().ok()).filter(|&cell| cell < 81);
+            let probability = parts.next().and_then(|probability| probability.parse::<f32>().ok());
+
+            match (cell, probability) {
+                (Some(cell), Some(probability)) => Ok((cell_action(cell, full_board), probability)),
+                _ => Err(format!("invalid policy entry '{}'", entry)),
+            }
+        }).collect::<Result<Vec<_>, _>>()?;
+
+        Ok(PositionRecord { game_state, policy, result })
+    }
+}
+
+#[derive(Default, Debug)]
+pub struct SelfPlayStats {
+    pub player1_wins: usize,
+    pub player2_wins: usize,
+    pub draws: usize,
+    pub positions: usize,
+}
+
+/// Plays an actor against itself and records every position it searched.
+///
+/// The first `random_plies` moves of every game are played uniformly at random
+/// and not recorded, to diversify the openings. For the next
+/// `temperature_plies` moves the actor's policy is sampled with the given
+/// temperature; after that its most likely move is played.
+pub struct SelfPlay {
+    games: usize,
+    /// The position every game starts from.
+    initial_state: GameState,
+    random_plies: usize,
+    temperature: f32,
+    temperature_plies: usize,
+    seed: Option<u64>,
+}
+
+impl SelfPlay {
+    pub fn new(games: usize) -> SelfPlay {
+        SelfPlay {
+            games,
+            initial_state: GameState::new(),
+            random_plies: 0,
+            temperature: 1.,
+            temperature_plies: 10,
+            seed: None,
+        }
+    }
+
+    /// Starts every game from `initial_state`, which must be on the standard
+    /// board, as position records encode moves by their cell on it.
+    pub fn with_initial_state(mut self, initial_state: GameState) -> SelfPlay {
+        assert!(initial_state.rules().is_standard_size(), "self-play only records positions on the standard board");
+        self.initial_state = initial_state;
+        self
+    }
+
+    pub fn with_random_plies(mut self, random_plies: usize) -> SelfPlay {
+        self.random_plies = random_plies;
+        self
+    }
+
+    pub fn with_temperature(mut self, temperature: f32, temperature_plies: usize) -> SelfPlay {
+        self.temperature = temperature;
+        self.temperature_plies = temperature_plies;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> SelfPlay {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Plays the games and writes a line for every recorded position. An actor
+    /// that fails to give a policy stops the run with an error.
+    pub fn run<W: Write>(&self, actor: &mut dyn Actor, writer: &mut W) -> io::Result<SelfPlayStats> {
+        let mut rng = match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        let mut stats = SelfPlayStats::default();
+
+        for _ in 0..self.games {
+            let (records, result) = self.play_game(actor, &mut rng)
+                .map_err(|error| io::Error::new(io::ErrorKind::Other, error))?;
+
+            for record in records {
+                writeln!(writer, "{}", record.to_line())?;
+                stats.positions += 1;
+            }
+
+            match result {
+                GameResult::Player1Wins => stats.player1_wins += 1,
+                GameResult::Player2Wins => stats.player2_wins += 1,
+                GameResult::Draw        => stats.draws += 1,
+            }
+        }
+
+        writer.flush()?;
+        Ok(stats)
+    }
+
+    fn play_game<R: Rng>(&self, actor: &mut dyn Actor, rng: &mut R) -> Result<(Vec<PositionRecord>, GameResult), ActorError> {
+        let mut game_state = self.initial_state.clone();
+        let mut game = GameRecord::new(game_state.clone());
+        let mut records = Vec::new();
+        let mut ply = 0;
+
+        loop {
+            let action = if ply < self.random_plies {
+                game_state.possible_actions().choose(rng).unwrap().clone()
+            } else {
+                let policy = actor.get_policy(&game_state, &Context::new(&game))?;
+                let temperature = if ply < self.random_plies + self.temperature_plies { self.temperature } else { 0. };
+                let action = select(&policy, temperature, rng);
+                records.push((game_state.clone(), policy));
+                action
+            };
+
+            let result = action.apply(&mut game_state);
+            game.push(action, result);
+
+            if let Some(result) = result {
+                let records = records.into_iter()
+                    .map(|(game_state, policy)| PositionRecord { game_state, policy, result })
+                    .collect();
+
+                return Ok((records, result));
+            }
+
+            ply += 1;
+        }
+    }
+}
+
+/// Picks a move from a policy: the most likely one at temperature 0, otherwise
+/// sampled proportionally to `probability ^ (1 / temperature)`.
+pub fn select<R: Rng>(policy: &[(Action, f32)], temperature: f32, rng: &mut R) -> Action {
+    if temperature > 0. {
+        let weights = policy.iter().map(|(_, probability)| probability.powf(1. / temperature));
+
+        if let Ok(distribution) = WeightedIndex::new(weights) {
+            return policy[distribution.sample(rng)].0.clone();
+        }
+    }
+
+    let (action, _) = policy.iter().max_by_key(|(_, probability)| {
+        NonNan::new(*probability).unwrap()
+    }).unwrap();
+
+    action.clone()
+}
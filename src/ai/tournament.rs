@@ -0,0 +1,303 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+use rand::prelude::*;
+use rand::rngs::StdRng;
+
+use crate::actor::{Actor, CancelToken};
+use crate::game::game_result::GameResult;
+use crate::game::game_state::GameState;
+use crate::game::player::Player;
+use crate::game::record::GameRecord;
+use crate::game::runner::{Match, Setup, Starter};
+
+/// How often a random opening is drawn again before giving up on it.
+const OPENING_TRIES: usize = 1000;
+
+/// Wins, draws and losses, counted for the first actor of a tournament.
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
+pub struct Score {
+    pub wins: usize,
+    pub draws: usize,
+    pub losses: usize,
+    /// Games that ended without a result, such as those in which an actor
+    /// failed. They do not count towards the points.
+    pub unfinished: usize,
+}
+
+impl Score {
+    /// The games with a result.
+    pub fn games(&self) -> usize {
+        self.wins + self.draws + self.losses
+    }
+
+    /// The average points per game, a win being 1 and a draw 0.5.
+    pub fn points(&self) -> f64 {
+        if self.games() == 0 {
+            return 0.5;
+        }
+        (self.wins as f64 + 0.5 * self.draws as f64) / self.games() as f64
+    }
+
+    /// The variance of the points of a single game.
+    fn variance(&self) -> f64 {
+        let points = self.points();
+        let squares = self.wins as f64 * (1. - points).powi(2)
+            + self.draws as f64 * (0.5 - points).powi(2)
+            + self.losses as f64 * points.powi(2);
+        squares / self.games().max(1) as f64
+    }
+
+    /// The Elo difference these points suggest, with the half-width of its 95%
+    /// confidence interval. Both are infinite when one side won every game.
+    pub fn elo(&self) -> (f64, f64) {
+        let points = self.points();
+        let margin = 1.96 * (self.variance() / self.games().max(1) as f64).sqrt();
+        let (low, high) = (elo(points - margin), elo(points + margin));
+
+        (elo(points), (high - low) / 2.)
+    }
+
+    fn add(&mut self, result: Option<GameResult>, first: Player) {
+        match result {
+            None => self.unfinished += 1,
+            Some(GameResult::Draw) => self.draws += 1,
+            Some(result) if result == first.wins() => self.wins += 1,
+            Some(_) => self.losses += 1,
+        }
+    }
+}
+
+/// The Elo difference at which a player expects these points per game.
+pub fn elo(points: f64) -> f64 {
+    if points <= 0. {
+        f64::NEG_INFINITY
+    } else if points >= 1. {
+        f64::INFINITY
+    } else {
+        -400. * (1. / points - 1.).log10()
+    }
+}
+
+/// The points per game a player expects at this Elo difference.
+pub fn expected_points(elo: f64) -> f64 {
+    1. / (1. + 10f64.powf(-elo / 400.))
+}
+
+/// What a sequential probability ratio test concluded so far.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Verdict {
+    /// More games are needed.
+    Continue,
+    /// The first actor is no stronger than `elo0`.
+    AcceptH0,
+    /// The first actor is stronger by at least `elo1`.
+    AcceptH1,
+}
+
+/// A sequential probability ratio test of whether the first actor is `elo1`
+/// stronger rather than only `elo0`, stopping as soon as the games say which
+/// with error rates `alpha` and `beta`.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Sprt {
+    pub elo0: f64,
+    pub elo1: f64,
+    /// The chance of accepting H1 when H0 holds.
+    pub alpha: f64,
+    /// The chance of accepting H0 when H1 holds.
+    pub beta: f64,
+}
+
+impl Sprt {
+    pub fn new(elo0: f64, elo1: f64) -> Sprt {
+        Sprt { elo0, elo1, alpha: 0.05, beta: 0.05 }
+    }
+
+    pub fn with_errors(mut self, alpha: f64, beta: f64) -> Sprt {
+        self.alpha = alpha;
+        self.beta = beta;
+        self
+    }
+
+    /// The log-likelihood ratio of H1 against H0, in the usual normal
+    /// approximation. It stays 0 until the games differ in outcome.
+    pub fn llr(&self, score: &Score) -> f64 {
+        let variance = score.variance();
+        if variance <= 0. {
+            return 0.;
+        }
+
+        let (points0, points1) = (expected_points(self.elo0), expected_points(self.elo1));
+        score.games() as f64 * (points1 - points0) * (2. * score.points() - points0 - points1) / (2. * variance)
+    }
+
+    /// The LLR at or below which H0 is accepted, and at or above which H1 is.
+    pub fn bounds(&self) -> (f64, f64) {
+        ((self.beta / (1. - self.alpha)).ln(), ((1. - self.beta) / self.alpha).ln())
+    }
+
+    pub fn verdict(&self, score: &Score) -> Verdict {
+        let llr = self.llr(score);
+        let (lower, upper) = self.bounds();
+
+        if llr >= upper {
+            Verdict::AcceptH1
+        } else if llr <= lower {
+            Verdict::AcceptH0
+        } else {
+            Verdict::Continue
+        }
+    }
+}
+
+/// How a tournament went.
+pub struct TournamentReport {
+    pub score: Score,
+    /// The games played, unfinished ones included, in the order they were
+    /// started.
+    pub records: Vec<GameRecord>,
+    /// The SPRT's conclusion, if the tournament ran one.
+    pub verdict: Option<Verdict>,
+}
+
+/// Plays many games between two actors to tell which is stronger. Each
+/// opening is played twice, once with each actor moving first. Games can run
+/// in parallel, each thread with its own actors, and an SPRT can stop the
+/// tournament as soon as it is decided.
+pub struct Tournament {
+    /// The players' names and symbols and the position games start from.
+    setup: Setup,
+    games: usize,
+    threads: usize,
+    opening_plies: usize,
+    seed: Option<u64>,
+    sprt: Option<Sprt>,
+}
+
+impl Tournament {
+    pub fn new(setup: Setup, games: usize) -> Tournament {
+        Tournament { setup, games, threads: 1, opening_plies: 0, seed: None, sprt: None }
+    }
+
+    /// Plays this many games at once.
+    pub fn with_threads(mut self, threads: usize) -> Tournament {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Starts each pair of games with this many random moves, so that
+    /// deterministic actors do not play the same game over and over.
+    pub fn with_random_openings(mut self, plies: usize) -> Tournament {
+        self.opening_plies = plies;
+        self
+    }
+
+    /// Picks the same openings on every run.
+    pub fn with_seed(mut self, seed: u64) -> Tournament {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Stops once this test is decided, or at the number of games otherwise.
+    pub fn with_sprt(mut self, sprt: Sprt) -> Tournament {
+        self.sprt = Some(sprt);
+        self
+    }
+
+    /// A random opening for every pair of games. Openings that end the game
+    /// are drawn again, up to `OPENING_TRIES` times.
+    fn openings(&self) -> Result<Vec<GameState>, String> {
+        let mut rng = match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+
+        (0..self.games.div_ceil(2)).map(|_| {
+            for _ in 0..OPENING_TRIES {
+                let mut game_state = self.setup.initial_state.clone();
+                let mut result = None;
+                for _ in 0..self.opening_plies {
+                    result = game_state.possible_actions().choose(&mut rng).unwrap().apply(&mut game_state);
+                    if result.is_some() {
+                        break;
+                    }
+                }
+
+                if result.is_none() {
+                    return Ok(game_state);
+                }
+            }
+
+            Err(format!("no opening of {} random moves leaves the game undecided", self.opening_plies))
+        }).collect()
+    }
+
+    /// Runs the tournament with actors made by `first` and `second`, calling
+    /// `on_game` with every game played and the score so far. Once the SPRT
+    /// is decided, games still being played are stopped and not counted.
+    /// Fails if no opening of the requested length leaves the game undecided.
+    pub fn run(
+        &self,
+        first: &(dyn Fn() -> Box<dyn Actor> + Sync),
+        second: &(dyn Fn() -> Box<dyn Actor> + Sync),
+        on_game: &mut dyn FnMut(&GameRecord, &Score),
+    ) -> Result<TournamentReport, String> {
+        let openings = self.openings()?;
+        let next = AtomicUsize::new(0);
+        let cancel = CancelToken::new();
+
+        let mut score = Score::default();
+        let mut records = Vec::new();
+        let mut verdict = self.sprt.map(|_| Verdict::Continue);
+
+        thread::scope(|scope| {
+            let (sender, receiver) = mpsc::channel();
+
+            for _ in 0..self.threads {
+                let (sender, openings, next, cancel) = (sender.clone(), &openings, &next, &cancel);
+                scope.spawn(move || {
+                    let (mut first, mut second) = (first(), second());
+
+                    loop {
+                        let game = next.fetch_add(1, Ordering::Relaxed);
+                        if game >= self.games || cancel.is_cancelled() {
+                            break;
+                        }
+
+                        let opening = &openings[game / 2];
+                        let starter = if game % 2 == 0 { Starter::First } else { Starter::Second };
+                        let first_player = if starter == Starter::First { opening.current_player() } else { opening.current_player().next() };
+
+                        let setup = self.setup.clone().with_initial_state(opening.clone()).with_starter(starter);
+                        let record = Match::new(setup).with_cancel(cancel.clone()).play(first.as_mut(), second.as_mut());
+                        if sender.send((game, first_player, record)).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+            drop(sender);
+
+            for (game, first_player, record) in receiver {
+                if cancel.is_cancelled() {
+                    continue;
+                }
+
+                score.add(record.result, first_player);
+                on_game(&record, &score);
+                records.push((game, record));
+
+                if let Some(sprt) = &self.sprt {
+                    verdict = Some(sprt.verdict(&score));
+                    if verdict != Some(Verdict::Continue) {
+                        cancel.cancel();
+                    }
+                }
+            }
+        });
+
+        records.sort_by_key(|(game, _)| *game);
+        Ok(TournamentReport { score, records: records.into_iter().map(|(_, record)| record).collect(), verdict })
+    }
+}
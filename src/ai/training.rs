@@ -0,0 +1,284 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::ai::nn::{cell_action, cell_index, encode, Layer, Network, CELLS, INPUT_PLANES};
+use crate::ai::selfplay::PositionRecord;
+use crate::game::symmetry::{Symmetry, SYMMETRIES};
+
+pub fn load_records<P: AsRef<Path>>(path: P) -> io::Result<Vec<PositionRecord>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut records = Vec::new();
+
+    for (number, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let record = PositionRecord::from_line(&line).map_err(|message| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {}", number + 1, message))
+        })?;
+        records.push(record);
+    }
+
+    Ok(records)
+}
+
+/// A training example: the encoded input planes, the target distribution over
+/// all cells and the target value for the player to move.
+#[derive(Clone)]
+pub struct Sample {
+    pub input: Vec<f32>,
+    pub policy: Vec<f32>,
+    pub value: f32,
+}
+
+impl Sample {
+    pub fn new(record: &PositionRecord) -> Sample {
+        let mut policy = vec![0.; CELLS];
+        for (action, probability) in record.policy.iter() {
+            policy[cell_index(action)] += probability;
+        }
+
+        Sample {
+            input: encode(&record.game_state),
+            policy,
+            value: 2. * record.result.score(record.game_state.current_player()) - 1.,
+        }
+    }
+
+    pub fn transformed(&self, symmetry: Symmetry) -> Sample {
+        let mut input = vec![0.; self.input.len()];
+        let mut policy = vec![0.; CELLS];
+
+        for cell in 0..CELLS {
+            let image = cell_index(&symmetry.transform_action(&cell_action(cell, false), 3));
+
+            for plane in 0..INPUT_PLANES {
+                input[plane * CELLS + image] = self.input[plane * CELLS + cell];
+            }
+            policy[image] = self.policy[cell];
+        }
+
+        Sample { input, policy, value: self.value }
+    }
+}
+
+/// Turns records into samples, adding the images of every position under all
+/// 8 symmetries if `augment` is set.
+pub fn samples(records: &[PositionRecord], augment: bool) -> Vec<Sample> {
+    let mut samples = Vec::with_capacity(records.len() * if augment { 8 } else { 1 });
+
+    for record in records {
+        let sample = Sample::new(record);
+
+        if augment {
+            for &symmetry in SYMMETRIES[1..].iter() {
+                samples.push(sample.transformed(symmetry));
+            }
+        }
+
+        samples.push(sample);
+    }
+
+    samples
+}
+
+/// Trains a network on policy cross-entropy plus value mean squared error with
+/// the Adam optimiser.
+pub struct Trainer {
+    network: Network,
+    learning_rate: f32,
+    weight_decay: f32,
+    first_moment: Network,
+    second_moment: Network,
+    steps: i32,
+}
+
+const BETA1: f32 = 0.9;
+const BETA2: f32 = 0.999;
+const EPSILON: f32 = 1e-8;
+
+impl Trainer {
+    pub fn new(network: Network) -> Trainer {
+        Trainer {
+            first_moment: zeroed(&network),
+            second_moment: zeroed(&network),
+            network,
+            learning_rate: 0.001,
+            weight_decay: 0.0001,
+            steps: 0,
+        }
+    }
+
+    pub fn with_learning_rate(mut self, learning_rate: f32) -> Trainer {
+        self.learning_rate = learning_rate;
+        self
+    }
+
+    pub fn with_weight_decay(mut self, weight_decay: f32) -> Trainer {
+        self.weight_decay = weight_decay;
+        self
+    }
+
+    pub fn network(&self) -> &Network {
+        &self.network
+    }
+
+    pub fn into_network(self) -> Network {
+        self.network
+    }
+
+    /// Shuffles the samples and does one pass over them, returning the mean
+    /// policy and value losses.
+    pub fn train_epoch<R: Rng>(&mut self, samples: &mut [Sample], batch_size: usize, rng: &mut R) -> (f32, f32) {
+        samples.shuffle(rng);
+
+        let mut policy_loss = 0.;
+        let mut value_loss = 0.;
+        for batch in samples.chunks(batch_size.max(1)) {
+            let (batch_policy_loss, batch_value_loss) = self.train_batch(batch);
+            policy_loss += batch_policy_loss * batch.len() as f32;
+            value_loss += batch_value_loss * batch.len() as f32;
+        }
+
+        let count = samples.len().max(1) as f32;
+        (policy_loss / count, value_loss / count)
+    }
+
+    pub fn train_batch(&mut self, batch: &[Sample]) -> (f32, f32) {
+        let mut gradient = zeroed(&self.network);
+        let mut policy_loss = 0.;
+        let mut value_loss = 0.;
+
+        for sample in batch {
+            let (sample_policy_loss, sample_value_loss) = backpropagate(&self.network, sample, &mut gradient);
+            policy_loss += sample_policy_loss;
+            value_loss += sample_value_loss;
+        }
+
+        self.steps += 1;
+        let scale = 1. / batch.len().max(1) as f32;
+        let correction1 = 1. - BETA1.powi(self.steps);
+        let correction2 = 1. - BETA2.powi(self.steps);
+
+        let layers = layers_mut(&mut self.network).into_iter()
+            .zip(layers_mut(&mut gradient))
+            .zip(layers_mut(&mut self.first_moment))
+            .zip(layers_mut(&mut self.second_moment));
+
+        for (((layer, gradient), first), second) in layers {
+            let decay = self.weight_decay;
+            let parameters = layer.weights.iter_mut().map(|p| (p, decay))
+                .chain(layer.biases.iter_mut().map(|p| (p, 0.)));
+            let gradients = gradient.weights.iter().chain(gradient.biases.iter());
+            let firsts = first.weights.iter_mut().chain(first.biases.iter_mut());
+            let seconds = second.weights.iter_mut().chain(second.biases.iter_mut());
+
+            for ((((parameter, decay), g), m), v) in parameters.zip(gradients).zip(firsts).zip(seconds) {
+                let g = g * scale + decay * *parameter;
+                *m = BETA1 * *m + (1. - BETA1) * g;
+                *v = BETA2 * *v + (1. - BETA2) * g * g;
+                *parameter -= self.learning_rate * (*m / correction1) / ((*v / correction2).sqrt() + EPSILON);
+            }
+        }
+
+        (policy_loss * scale, value_loss * scale)
+    }
+}
+
+fn zeroed(network: &Network) -> Network {
+    let mut zeroed = network.clone();
+
+    for layer in layers_mut(&mut zeroed) {
+        layer.weights.iter_mut().for_each(|w| *w = 0.);
+        layer.biases.iter_mut().for_each(|b| *b = 0.);
+    }
+
+    zeroed
+}
+
+fn layers_mut(network: &mut Network) -> Vec<&mut Layer> {
+    let mut layers: Vec<&mut Layer> = network.trunk.iter_mut().collect();
+    layers.push(&mut network.policy);
+    layers.push(&mut network.value);
+    layers
+}
+
+/// Adds the gradient of the loss on one sample to `gradient` and returns the
+/// policy and value losses. The policy softmax only ranges over the legal moves,
+/// as in `Network::predict`.
+fn backpropagate(network: &Network, sample: &Sample, gradient: &mut Network) -> (f32, f32) {
+    let mut activations = vec![sample.input.clone()];
+    for layer in network.trunk.iter() {
+        let mut activation = layer.forward(activations.last().unwrap());
+        activation.iter_mut().for_each(|a| *a = a.max(0.));
+        activations.push(activation);
+    }
+
+    let features = activations.last().unwrap();
+    let logits = network.policy.forward(features);
+    let value = network.value.forward(features)[0].tanh();
+
+    let legal = &sample.input[2 * CELLS..3 * CELLS];
+    let max = (0..CELLS).filter(|&cell| legal[cell] > 0.).map(|cell| logits[cell]).fold(f32::NEG_INFINITY, f32::max);
+    let mut probabilities = vec![0.; CELLS];
+    let mut total = 0.;
+    for cell in (0..CELLS).filter(|&cell| legal[cell] > 0.) {
+        probabilities[cell] = (logits[cell] - max).exp();
+        total += probabilities[cell];
+    }
+
+    let mut policy_loss = 0.;
+    let mut logits_gradient = vec![0.; CELLS];
+    for cell in (0..CELLS).filter(|&cell| legal[cell] > 0.) {
+        probabilities[cell] /= total;
+        policy_loss -= sample.policy[cell] * probabilities[cell].max(1e-12).ln();
+        logits_gradient[cell] = probabilities[cell] - sample.policy[cell];
+    }
+
+    let value_loss = (value - sample.value).powi(2);
+    let value_gradient = [2. * (value - sample.value) * (1. - value * value)];
+
+    let mut features_gradient = vec![0.; features.len()];
+    accumulate(&network.policy, &mut gradient.policy, features, &logits_gradient, &mut features_gradient);
+    accumulate(&network.value, &mut gradient.value, features, &value_gradient, &mut features_gradient);
+
+    let mut output_gradient = features_gradient;
+    for (index, layer) in network.trunk.iter().enumerate().rev() {
+        let output = &activations[index + 1];
+        for (g, &a) in output_gradient.iter_mut().zip(output) {
+            if a <= 0. {
+                *g = 0.;
+            }
+        }
+
+        let mut input_gradient = vec![0.; layer.inputs];
+        accumulate(layer, &mut gradient.trunk[index], &activations[index], &output_gradient, &mut input_gradient);
+        output_gradient = input_gradient;
+    }
+
+    (policy_loss, value_loss)
+}
+
+fn accumulate(layer: &Layer, gradient: &mut Layer, input: &[f32], output_gradient: &[f32], input_gradient: &mut [f32]) {
+    for (o, &g) in output_gradient.iter().enumerate() {
+        if g == 0. {
+            continue;
+        }
+
+        gradient.biases[o] += g;
+        let row = o * layer.inputs..(o + 1) * layer.inputs;
+        for ((w, gw), (i, gi)) in layer.weights[row.clone()].iter()
+            .zip(gradient.weights[row].iter_mut())
+            .zip(input.iter().zip(input_gradient.iter_mut())) {
+
+            *gw += g * i;
+            *gi += g * w;
+        }
+    }
+}
@@ -0,0 +1,515 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::time::Duration;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::actor::{Actor, Context};
+use crate::ai::analysis::{self, Judgement};
+use crate::ai::baseline::{GreedyActor, RandomActor};
+use crate::ai::evaluator::HeuristicEvaluator;
+use crate::ai::mcts::MCTSActor;
+use crate::ai::nn::Network;
+use crate::ai::puct::PUCTActor;
+use crate::ai::selfplay::SelfPlay;
+use crate::ai::tournament::{Sprt, Tournament, Verdict};
+use crate::game::game_state::GameState;
+use crate::game::notation::{action_to_notation, from_notation};
+use crate::game::player::PlayerConfig;
+use crate::game::record::GameRecord;
+#[cfg(feature = "tui")]
+use crate::gui::tui::{self, Screen};
+use crate::gui::render::RenderOptions;
+use crate::gui::{Human, Match, Printer, Setup, Starter};
+
+pub const USAGE: &str = "\
+Usage: ultimate_ttt <command> [options]
+
+Commands:
+  play        Play a game on the command line (default)
+  analyse     Print the engine's view of a position
+  selfplay    Generate training data from engine self-play
+  match       Play a series of games between two actors
+  review      Mark the mistakes and blunders in a saved game
+
+Options:
+  --first <actor>       Actor for the first side: human, mcts, heuristic, nn,
+                        random or greedy
+                        (default: mcts, or human for play)
+  --second <actor>      Actor for the second side (default: mcts)
+  --starter <side>      Who moves first: first, second or random (default: first)
+  --time <ms>           Engine time per move in milliseconds (default: 1000)
+  --iterations <n>      Engine simulations per move, instead of a time limit
+  --threads <n>         Engine search threads (default: 1)
+  --network <file>      Network weights for the nn actor
+  --seed <n>            Seed for side assignment, match openings, self-play
+                        move sampling and the random and greedy actors; engine
+                        playouts are not seeded
+  --position <notation> Starting position in position notation
+  --games <n>           Number of games for selfplay and match (default: 10)
+  --parallel <n>        Games match plays at once (default: 1)
+  --openings <n>        Random moves to start each pair of match games with
+                        (default: 0)
+  --sprt <elo0>,<elo1>  Stop the match once an SPRT decides whether the first
+                        actor is elo1 rather than elo0 stronger
+  --second-time <ms>    Engine time per move for the second actor only
+  --second-iterations <n>
+                        Engine simulations per move for the second actor only
+  --input <file>        The game record to review
+  --output <file>       Where to write the game record, analysis or training data
+  --delay <ms>          Pause after every move of play, to follow engine games
+  --plain               Play in plain text instead of the full-screen interface
+  --unicode             Draw the plain-text board with box-drawing characters
+  --help                Show this message";
+
+/// The kinds of actor the command line can set up.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ActorKind {
+    Human,
+    /// MCTS with random playouts.
+    Mcts,
+    /// MCTS with the heuristic evaluator at the leaves.
+    Heuristic,
+    /// PUCT guided by a network loaded from `--network`.
+    Network,
+    /// Uniformly random moves.
+    Random,
+    /// One move of lookahead for sub-boards won and given away.
+    Greedy,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Command {
+    Play,
+    Analyse,
+    SelfPlay,
+    Match,
+    Review,
+    Help,
+}
+
+#[derive(Clone)]
+pub struct Options {
+    pub command: Command,
+    pub first: ActorKind,
+    pub second: ActorKind,
+    pub starter: Starter,
+    pub time: u128,
+    pub iterations: Option<usize>,
+    pub threads: usize,
+    pub network: Option<String>,
+    pub seed: Option<u64>,
+    pub position: GameState,
+    pub games: usize,
+    /// How many games `match` plays at once.
+    pub parallel: usize,
+    /// Random moves at the start of each pair of `match` games.
+    pub openings: usize,
+    pub sprt: Option<(f64, f64)>,
+    /// Search limits for the second actor, where they differ from the first's.
+    pub second_time: Option<u128>,
+    pub second_iterations: Option<usize>,
+    /// Milliseconds to pause after every move of `play`.
+    pub delay: u64,
+    pub input: Option<String>,
+    pub output: Option<String>,
+    /// Use the plain-text interface even where the full-screen one works.
+    pub plain: bool,
+    pub unicode: bool,
+}
+
+/// Parses the arguments after the program name.
+pub fn parse(args: &[String]) -> Result<Options, String> {
+    let mut args = args.iter().peekable();
+
+    let command = match args.peek().map(|arg| arg.as_str()) {
+        Some("play") => Command::Play,
+        Some("analyse") | Some("analyze") => Command::Analyse,
+        Some("selfplay") => Command::SelfPlay,
+        Some("match") => Command::Match,
+        Some("review") => Command::Review,
+        Some("help") => Command::Help,
+        Some(arg) if !arg.starts_with("--") => return Err(format!("unknown command '{}'", arg)),
+        _ => Command::Play,
+    };
+    if args.peek().is_some_and(|arg| !arg.starts_with("--")) {
+        args.next();
+    }
+
+    let mut options = Options {
+        command,
+        first: if command == Command::Play { ActorKind::Human } else { ActorKind::Mcts },
+        second: ActorKind::Mcts,
+        starter: Starter::First,
+        time: 1000,
+        iterations: None,
+        threads: 1,
+        network: None,
+        seed: None,
+        position: GameState::new(),
+        games: 10,
+        parallel: 1,
+        openings: 0,
+        sprt: None,
+        second_time: None,
+        second_iterations: None,
+        delay: 0,
+        input: None,
+        output: None,
+        plain: false,
+        unicode: false,
+    };
+
+    while let Some(arg) = args.next() {
+        if arg == "--help" {
+            options.command = Command::Help;
+            continue;
+        }
+        if arg == "--plain" {
+            options.plain = true;
+            continue;
+        }
+        if arg == "--unicode" {
+            options.unicode = true;
+            continue;
+        }
+
+        let mut value = || args.next().ok_or_else(|| format!("missing value for {}", arg));
+
+        match arg.as_str() {
+            "--first" => options.first = parse_actor(value()?)?,
+            "--second" => options.second = parse_actor(value()?)?,
+            "--starter" => options.starter = match value()?.as_str() {
+                "first" => Starter::First,
+                "second" => Starter::Second,
+                "random" => Starter::Random,
+                other => return Err(format!("invalid starter '{}'", other)),
+            },
+            "--time" => options.time = parse_number(arg, value()?)?,
+            "--iterations" => options.iterations = Some(parse_number(arg, value()?)?),
+            "--threads" => options.threads = parse_number(arg, value()?)?,
+            "--network" => options.network = Some(value()?.clone()),
+            "--seed" => options.seed = Some(parse_number(arg, value()?)?),
+            "--position" => options.position = from_notation(value()?)?,
+            "--games" => options.games = parse_number(arg, value()?)?,
+            "--parallel" => options.parallel = parse_number(arg, value()?)?,
+            "--openings" => options.openings = parse_number(arg, value()?)?,
+            "--sprt" => options.sprt = Some(parse_sprt(value()?)?),
+            "--second-time" => options.second_time = Some(parse_number(arg, value()?)?),
+            "--second-iterations" => options.second_iterations = Some(parse_number(arg, value()?)?),
+            "--delay" => options.delay = parse_number(arg, value()?)?,
+            "--input" => options.input = Some(value()?.clone()),
+            "--output" => options.output = Some(value()?.clone()),
+            other => return Err(format!("unknown option '{}'", other)),
+        }
+    }
+
+    if options.command == Command::Help {
+        return Ok(options);
+    }
+
+    if options.position.is_over() {
+        return Err("the position is already decided".to_string());
+    }
+    if options.openings > 0 && options.openings >= options.position.empty_squares() {
+        return Err("--openings must be fewer than the empty squares of the position".to_string());
+    }
+    for &kind in [options.first, options.second].iter() {
+        if kind == ActorKind::Network && options.network.is_none() {
+            return Err("the nn actor needs --network".to_string());
+        }
+        if kind == ActorKind::Network && !options.position.rules().is_standard_size() {
+            return Err(NETWORK_BOARD.to_string());
+        }
+    }
+    if options.command == Command::SelfPlay && !options.position.rules().is_standard_size() {
+        return Err("selfplay only records positions on the standard board".to_string());
+    }
+    if options.command != Command::Play && (options.first == ActorKind::Human || options.second == ActorKind::Human) {
+        return Err("only play supports human players".to_string());
+    }
+
+    Ok(options)
+}
+
+const NETWORK_BOARD: &str = "the nn actor only plays on the standard board";
+
+fn parse_actor(kind: &str) -> Result<ActorKind, String> {
+    match kind {
+        "human" => Ok(ActorKind::Human),
+        "mcts" => Ok(ActorKind::Mcts),
+        "heuristic" => Ok(ActorKind::Heuristic),
+        "nn" => Ok(ActorKind::Network),
+        "random" => Ok(ActorKind::Random),
+        "greedy" => Ok(ActorKind::Greedy),
+        other => Err(format!("unknown actor '{}'", other)),
+    }
+}
+
+fn parse_sprt(value: &str) -> Result<(f64, f64), String> {
+    let bounds: Vec<&str> = value.split(',').collect();
+    match bounds.as_slice() {
+        [elo0, elo1] => Ok((parse_number("--sprt", elo0)?, parse_number("--sprt", elo1)?)),
+        _ => Err(format!("invalid value '{}' for --sprt, expected <elo0>,<elo1>", value)),
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(option: &str, value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("invalid value '{}' for {}", value, option))
+}
+
+impl Options {
+    /// MCTS with the search limits given on the command line, for the engine
+    /// actors, hints and reviews.
+    pub fn engine(&self) -> MCTSActor {
+        let mut engine = MCTSActor::new(self.time).with_threads(self.threads);
+        if let Some(iterations) = self.iterations {
+            engine = engine.with_iterations(iterations);
+        }
+        engine
+    }
+
+    pub fn actor(&self, kind: ActorKind) -> Result<Box<dyn Actor>, String> {
+        Ok(match kind {
+            ActorKind::Human => Box::new(Human::new().with_hint(self.engine())),
+            ActorKind::Mcts => Box::new(self.engine()),
+            ActorKind::Heuristic => Box::new(self.engine().with_evaluator(Box::new(HeuristicEvaluator::new()))),
+            ActorKind::Network => {
+                let path = self.network.as_ref().ok_or("the nn actor needs --network")?;
+                if !self.position.rules().is_standard_size() {
+                    return Err(NETWORK_BOARD.to_string());
+                }
+                let network = Network::load(path).map_err(|error| format!("cannot load {}: {}", path, error))?;
+                let mut actor = PUCTActor::new(network, self.time);
+                if let Some(iterations) = self.iterations {
+                    actor = actor.with_iterations(iterations);
+                }
+                Box::new(actor)
+            },
+            ActorKind::Random => match self.seed {
+                Some(seed) => Box::new(RandomActor::new().with_seed(seed)),
+                None => Box::new(RandomActor::new()),
+            },
+            ActorKind::Greedy => match self.seed {
+                Some(seed) => Box::new(GreedyActor::new().with_seed(seed)),
+                None => Box::new(GreedyActor::new()),
+            },
+        })
+    }
+
+    /// The options the second actor is made with.
+    fn second_side(&self) -> Options {
+        let mut options = self.clone();
+        options.time = self.second_time.unwrap_or(self.time);
+        options.iterations = self.second_iterations.or(self.iterations);
+        options
+    }
+
+    fn rng(&self) -> StdRng {
+        match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        }
+    }
+
+    fn name(kind: ActorKind) -> &'static str {
+        match kind {
+            ActorKind::Human => "You",
+            ActorKind::Mcts => "MCTS",
+            ActorKind::Heuristic => "Heuristic MCTS",
+            ActorKind::Network => "Network",
+            ActorKind::Random => "Random",
+            ActorKind::Greedy => "Greedy",
+        }
+    }
+
+    fn setup(&self) -> Setup {
+        let starter = match self.starter {
+            Starter::Random if self.rng().gen() => Starter::First,
+            Starter::Random => Starter::Second,
+            starter => starter,
+        };
+
+        Setup::new()
+            .with_players(PlayerConfig::new(Options::name(self.first), 'O'), PlayerConfig::new(Options::name(self.second), 'X'))
+            .with_starter(starter)
+            .with_initial_state(self.position.clone())
+    }
+
+    fn game<'a>(&self) -> Match<'a> {
+        Match::new(self.setup()).with_delay(Duration::from_millis(self.delay))
+    }
+}
+
+pub fn run(options: &Options) -> Result<(), String> {
+    match options.command {
+        Command::Play => play(options),
+        Command::Analyse => analyse(options),
+        Command::SelfPlay => self_play(options),
+        Command::Match => play_match(options),
+        Command::Review => review(options),
+        Command::Help => {
+            println!("{}", USAGE);
+            Ok(())
+        },
+    }
+}
+
+fn play(options: &Options) -> Result<(), String> {
+    let record = match play_full_screen(options) {
+        Some(record) => record?,
+        None => options.game()
+            .with_observer(Printer::new(RenderOptions::new().with_unicode(options.unicode)))
+            .play(options.actor(options.first)?.as_mut(), options.actor(options.second)?.as_mut()),
+    };
+
+    if let Some(path) = &options.output {
+        record.save(path).map_err(|error| format!("cannot write {}: {}", path, error))?;
+    }
+
+    Ok(())
+}
+
+/// Plays on the full screen where the terminal allows it, leaving the game to
+/// the plain-text interface otherwise.
+#[cfg(feature = "tui")]
+fn play_full_screen(options: &Options) -> Option<Result<GameRecord, String>> {
+    if options.plain || !tui::is_supported() {
+        return None;
+    }
+
+    Some(play_tui(options))
+}
+
+#[cfg(feature = "tui")]
+fn play_tui(options: &Options) -> Result<GameRecord, String> {
+    // Humans have no actor; the screen takes their moves.
+    let seat = |kind| if kind == ActorKind::Human { Ok(None) } else { options.actor(kind).map(Some) };
+    let (mut first, mut second) = (seat(options.first)?, seat(options.second)?);
+    let first = first.as_mut().map(|actor| actor.as_mut() as &mut dyn Actor);
+    let second = second.as_mut().map(|actor| actor.as_mut() as &mut dyn Actor);
+    let mut screen = Screen::new().with_hint(options.engine());
+
+    let record = tui::play(options.game(), &mut screen, first, second)
+        .map_err(|error| format!("terminal error: {}", error))?;
+    println!("Result: {}", record.describe_result());
+
+    Ok(record)
+}
+
+#[cfg(not(feature = "tui"))]
+fn play_full_screen(_options: &Options) -> Option<Result<GameRecord, String>> {
+    None
+}
+
+fn analyse(options: &Options) -> Result<(), String> {
+    let record = GameRecord::new(options.position.clone());
+    let mut policy = options.actor(options.first)?
+        .get_policy(&options.position, &Context::new(&record))
+        .map_err(|error| error.to_string())?;
+    policy.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap());
+
+    let lines: Vec<String> = policy.iter()
+        .take(5)
+        .map(|(action, share)| format!("{} {:.1}%", action_to_notation(action), 100. * share))
+        .collect();
+    println!("{}", lines.join("\n"));
+
+    if let Some(path) = &options.output {
+        std::fs::write(path, lines.join("\n") + "\n").map_err(|error| format!("cannot write {}: {}", path, error))?;
+    }
+
+    Ok(())
+}
+
+fn self_play(options: &Options) -> Result<(), String> {
+    let path = options.output.as_ref().ok_or("selfplay needs --output")?;
+    let mut writer = BufWriter::new(File::create(path).map_err(|error| format!("cannot create {}: {}", path, error))?);
+
+    let mut self_play = SelfPlay::new(options.games).with_initial_state(options.position.clone());
+    if let Some(seed) = options.seed {
+        self_play = self_play.with_seed(seed);
+    }
+
+    let stats = self_play.run(options.actor(options.first)?.as_mut(), &mut writer)
+        .and_then(|stats| writer.flush().map(|_| stats))
+        .map_err(|error| format!("cannot write {}: {}", path, error))?;
+    println!("{:?}", stats);
+
+    Ok(())
+}
+
+/// Plays `games` games, the actors taking turns to start.
+fn play_match(options: &Options) -> Result<(), String> {
+    let second_side = options.second_side();
+    // Every game thread makes its own actors, so check that they can be made
+    // before starting.
+    options.actor(options.first)?;
+    second_side.actor(options.second)?;
+    let first = || options.actor(options.first).expect("checked above");
+    let second = || second_side.actor(options.second).expect("checked above");
+
+    let sprt = options.sprt.map(|(elo0, elo1)| Sprt::new(elo0, elo1));
+    let mut tournament = Tournament::new(options.setup(), options.games)
+        .with_threads(options.parallel)
+        .with_random_openings(options.openings);
+    if let Some(seed) = options.seed {
+        tournament = tournament.with_seed(seed);
+    }
+    if let Some(sprt) = sprt {
+        tournament = tournament.with_sprt(sprt);
+    }
+
+    let report = tournament.run(&first, &second, &mut |record, score| {
+        println!("Game {}: {}", score.games() + score.unfinished, record.describe_result());
+    })?;
+
+    let score = report.score;
+    let (elo, error) = score.elo();
+    println!("{} wins, {} draws, {} losses for the first actor", score.wins, score.draws, score.losses);
+    if score.unfinished > 0 {
+        println!("{} games ended without a result and do not count", score.unfinished);
+    }
+    println!("Elo difference: {:+.1} ± {:.1}", elo, error);
+    if let (Some(sprt), Some(verdict)) = (sprt, report.verdict) {
+        let (lower, upper) = sprt.bounds();
+        let conclusion = match verdict {
+            Verdict::Continue => "undecided",
+            Verdict::AcceptH0 => "H0 accepted",
+            Verdict::AcceptH1 => "H1 accepted",
+        };
+        println!("SPRT [{}, {}]: LLR {:.2} ({:.2}, {:.2}), {}", sprt.elo0, sprt.elo1, sprt.llr(&score), lower, upper, conclusion);
+    }
+
+    if let Some(path) = &options.output {
+        let records: Vec<String> = report.records.iter().map(GameRecord::to_text).collect();
+        std::fs::write(path, records.join("\n")).map_err(|error| format!("cannot write {}: {}", path, error))?;
+    }
+
+    Ok(())
+}
+
+fn review(options: &Options) -> Result<(), String> {
+    let path = options.input.as_ref().ok_or("review needs --input")?;
+    let record = GameRecord::load(path).map_err(|error| format!("cannot read {}: {}", path, error))?;
+
+    let mut lines = Vec::with_capacity(record.moves.len() + 1);
+    for (index, review) in analysis::review(&record, &options.engine()).iter().enumerate() {
+        let mut line = format!("{:>3}. {} {}  win {:.0}%",
+                               index + 1, record.player(review.player).symbol, action_to_notation(&review.action), 100. * review.score);
+        if review.judgement != Judgement::Good {
+            line += &format!("  {:?} (best {}, win {:.0}%)",
+                             review.judgement, action_to_notation(&review.best.action), 100. * review.best.score);
+        }
+        lines.push(line);
+    }
+    lines.push(format!("Result: {}", record.describe_result()));
+    println!("{}", lines.join("\n"));
+
+    if let Some(path) = &options.output {
+        std::fs::write(path, lines.join("\n") + "\n").map_err(|error| format!("cannot write {}: {}", path, error))?;
+    }
+
+    Ok(())
+}
@@ -2,4 +2,9 @@ pub mod action;
 pub mod board;
 pub mod game_result;
 pub mod game_state;
+pub mod notation;
 pub mod player;
+pub mod record;
+pub mod rules;
+pub mod runner;
+pub mod symmetry;
@@ -1,7 +1,7 @@
 use crate::game::game_state::GameState;
 use crate::game::game_result::GameResult;
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub struct Action {
     pub sub_x: usize,
     pub sub_y: usize,
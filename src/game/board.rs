@@ -1,4 +1,24 @@
 use crate::game::game_result::GameResult;
+use crate::game::rules::Rules;
+
+/// The largest supported board size. Boards are stored inline at this capacity
+/// so that they stay cheap to copy.
+pub const MAX_SIZE: usize = 4;
+const MAX_ITEMS: usize = MAX_SIZE * MAX_SIZE;
+
+const DIRECTIONS: [(isize, isize); 4] = [(1, 0), (0, 1), (1, 1), (1, -1)];
+
+/// The lines of the standard 3x3 board.
+pub const LINES: [[(usize, usize); 3]; 8] = [
+    [(0, 0), (1, 0), (2, 0)],
+    [(0, 1), (1, 1), (2, 1)],
+    [(0, 2), (1, 2), (2, 2)],
+    [(0, 0), (0, 1), (0, 2)],
+    [(1, 0), (1, 1), (1, 2)],
+    [(2, 0), (2, 1), (2, 2)],
+    [(0, 0), (1, 1), (2, 2)],
+    [(0, 2), (1, 1), (2, 0)],
+];
 
 pub trait Owned {
     fn result(&self) -> Option<GameResult>;
@@ -6,18 +26,26 @@ pub trait Owned {
     fn set_result(&mut self, result: Option<GameResult>);
 }
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct Board {
     structure: BoardStructure<SubBoard>,
 }
 
 impl Board {
     pub fn new() -> Board {
+        Board::with_size(3)
+    }
+
+    pub fn with_size(size: usize) -> Board {
         Board {
-            structure: <BoardStructure<SubBoard>>::new(),
+            structure: <BoardStructure<SubBoard>>::with_size(size),
         }
     }
 
+    pub fn size(&self) -> usize {
+        self.structure.size()
+    }
+
     pub fn structure(&self) -> &BoardStructure<SubBoard> {
         &self.structure
     }
@@ -34,23 +62,21 @@ impl Board {
         self.structure_mut().get_mut(x, y)
     }
 
-    pub fn make_move(&mut self, result: Option<GameResult>, sub_x: usize, sub_y: usize, x: usize, y: usize) -> (Option<usize>, Option<usize>, Option<GameResult>) {
+    pub fn make_move(&mut self, result: Option<GameResult>, sub_x: usize, sub_y: usize, x: usize, y: usize, rules: &Rules) -> (Option<usize>, Option<usize>, Option<GameResult>) {
+        let already_closed = self.get(sub_x, sub_y).result().is_some();
+
         self.get_mut(sub_x, sub_y)
             .structure_mut()
             .set_result_at(x, y, result);
 
         let mut game_result: Option<GameResult> = None;
 
-        if result.is_some() {
-            match self.get_mut(sub_x, sub_y).structure().check_result(x, y) {
+        if result.is_some() && !already_closed {
+            match self.get_mut(sub_x, sub_y).structure().check_result(x, y, rules) {
                 None => {},
                 Some(result) => {
                     self.get_mut(sub_x, sub_y).set_result(Some(result));
-
-                    match self.structure().check_result(sub_x, sub_y) {
-                        None => {},
-                        Some(result) => game_result = Some(result),
-                    }
+                    game_result = rules.game_result(self.structure(), sub_x, sub_y);
                 }
             }
         }
@@ -61,7 +87,13 @@ impl Board {
     }
 }
 
-#[derive(Copy, Clone)]
+impl Default for Board {
+    fn default() -> Board {
+        Board::new()
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
 pub struct SubBoard {
     structure: BoardStructure<Square>,
     result: Option<GameResult>,
@@ -69,7 +101,11 @@ pub struct SubBoard {
 
 impl SubBoard {
     pub fn new() -> SubBoard {
-        SubBoard { structure: <BoardStructure<Square>>::new(), result: None }
+        SubBoard::with_size(3)
+    }
+
+    pub fn with_size(size: usize) -> SubBoard {
+        SubBoard { structure: <BoardStructure<Square>>::with_size(size), result: None }
     }
 
     pub fn structure(&self) -> &BoardStructure<Square> {
@@ -89,6 +125,12 @@ impl SubBoard {
     }
 }
 
+impl Default for SubBoard {
+    fn default() -> SubBoard {
+        SubBoard::new()
+    }
+}
+
 impl Owned for SubBoard {
     fn result(&self) -> Option<GameResult> {
         self.result
@@ -99,54 +141,124 @@ impl Owned for SubBoard {
     }
 }
 
-#[derive(Copy, Clone)]
+/// A `size` by `size` grid, stored row by row.
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
 pub struct BoardStructure<T: Owned> {
-    pub items: [T; 9],
+    items: [T; MAX_ITEMS],
+    size: u8,
 }
 
 impl<T: Owned> BoardStructure<T> {
+    pub fn size(&self) -> usize {
+        self.size as usize
+    }
+
+    pub fn items(&self) -> &[T] {
+        &self.items[..self.size() * self.size()]
+    }
+
     pub fn get(&self, x: usize, y: usize) -> &T {
-        &self.items[3 * y + x]
+        &self.items[self.size() * y + x]
     }
 
     pub fn get_mut(&mut self, x: usize, y: usize) -> &mut T {
-        &mut self.items[3 * y + x]
+        let size = self.size();
+        &mut self.items[size * y + x]
     }
 
     pub fn set_result_at(&mut self, x: usize, y: usize, result: Option<GameResult>) {
-        self.items.get_mut(3 * y + x).unwrap().set_result(result);
+        self.get_mut(x, y).set_result(result);
     }
 
-    pub fn check_result(&self, last_x: usize, last_y: usize) -> Option<GameResult> {
+    /// Checks whether the last move decided this board. Only lines through the
+    /// last move are looked at, and only lines owned by a single player count:
+    /// under the standard rules drawn sub-boards never complete a line. A board
+    /// that is full without such a line is a draw; for the macro board that
+    /// means the whole game is drawn once every sub-board is closed, unless
+    /// the rules break the tie. If the last move completes lines for both
+    /// players, which drawn sub-boards counting for both allows, it is a draw.
+    pub fn check_result(&self, last_x: usize, last_y: usize, rules: &Rules) -> Option<GameResult> {
+        // The standard board keeps an unrolled check, as it is on the playout
+        // hot path.
+        if self.size() != 3 || rules.win_length != 3 {
+            return self.check_result_general(last_x, last_y, rules);
+        }
+
+        let mut owners = [None; 4];
+        let items = &self.items;
+
         if ((last_x == 1) as usize + (last_y == 1) as usize) != 1 {
-            if last_x == last_y &&
-                self.get(0, 0).result() == self.get(1, 1).result() &&
-                self.get(0, 0).result() == self.get(2, 2).result() {
+            if last_x == last_y {
+                owners[0] = rules.line_owner(&[items[0].result(), items[4].result(), items[8].result()]);
+            }
 
-                return self.get(0, 0).result();
+            if last_x == 2 - last_y {
+                owners[1] = rules.line_owner(&[items[6].result(), items[4].result(), items[2].result()]);
             }
+        }
+
+        owners[2] = rules.line_owner(&[items[last_x].result(), items[3 + last_x].result(), items[6 + last_x].result()]);
+        owners[3] = rules.line_owner(&[items[3 * last_y].result(), items[3 * last_y + 1].result(), items[3 * last_y + 2].result()]);
 
-            if last_x == 2 - last_y &&
-                self.get(0, 2).result() == self.get(1, 1).result() &&
-                self.get(0, 2).result() == self.get(2, 0).result() {
+        self.combine_owners(owners.iter().flatten().cloned())
+    }
 
-                return self.get(0, 2).result();
+    /// `check_result` for any size and line length: looks at every stretch of
+    /// `win_length` squares through the last move.
+    fn check_result_general(&self, last_x: usize, last_y: usize, rules: &Rules) -> Option<GameResult> {
+        let size = self.size() as isize;
+        let length = rules.win_length as isize;
+        let mut owners = Vec::new();
+        let mut results = Vec::with_capacity(rules.win_length);
+
+        for &(dx, dy) in DIRECTIONS.iter() {
+            for start in 0..length {
+                let (x, y) = (last_x as isize - start * dx, last_y as isize - start * dy);
+                let (end_x, end_y) = (x + (length - 1) * dx, y + (length - 1) * dy);
+                if x < 0 || y < 0 || x >= size || y >= size || end_x < 0 || end_y < 0 || end_x >= size || end_y >= size {
+                    continue;
+                }
+
+                results.clear();
+                results.extend((0..length).map(|i| self.get((x + i * dx) as usize, (y + i * dy) as usize).result()));
+                if let Some(owner) = rules.line_owner(&results) {
+                    owners.push(owner);
+                }
             }
         }
 
-        if self.get(last_x, 0).result() == self.get(last_x, 1).result() &&
-            self.get(last_x, 0).result() == self.get(last_x, 2).result() {
+        self.combine_owners(owners.into_iter())
+    }
+
+    fn combine_owners<I: Iterator<Item = GameResult>>(&self, owners: I) -> Option<GameResult> {
+        let mut winner = None;
+        for owner in owners {
+            if winner.is_some() && winner != Some(owner) {
+                return Some(GameResult::Draw);
+            }
 
-            return self.get(last_x, 0).result();
+            winner = Some(owner);
         }
 
-        if self.get(0, last_y).result() == self.get(1, last_y).result() &&
-            self.get(0, last_y).result() == self.get(2, last_y).result() {
+        winner.or_else(|| self.draw_if_full())
+    }
+
+    /// Determines the result from scratch by looking at every line, rather
+    /// than only those through the last move.
+    pub fn full_result(&self, rules: &Rules) -> Option<GameResult> {
+        for line in lines(self.size(), rules.win_length) {
+            let results: Vec<Option<GameResult>> = line.iter().map(|&(x, y)| self.get(x, y).result()).collect();
 
-            return self.get(0, last_y).result();
+            if let Some(owner) = rules.line_owner(&results) {
+                return Some(owner);
+            }
         }
 
-        if self.items.iter().any(|item| item.result() == None) {
+        self.draw_if_full()
+    }
+
+    fn draw_if_full(&self) -> Option<GameResult> {
+        if self.items().iter().any(|item| item.result().is_none()) {
             None
         } else {
             Some(GameResult::Draw)
@@ -154,23 +266,69 @@ impl<T: Owned> BoardStructure<T> {
     }
 }
 
+/// Every stretch of `win_length` squares in a row, column or diagonal of a
+/// `size` by `size` board.
+pub fn lines(size: usize, win_length: usize) -> Vec<Vec<(usize, usize)>> {
+    let mut lines = Vec::new();
+    let (size, length) = (size as isize, win_length as isize);
+
+    for y in 0..size {
+        for x in 0..size {
+            for &(dx, dy) in DIRECTIONS.iter() {
+                let (end_x, end_y) = (x + (length - 1) * dx, y + (length - 1) * dy);
+                if end_x >= 0 && end_y >= 0 && end_x < size && end_y < size {
+                    lines.push((0..length).map(|i| ((x + i * dx) as usize, (y + i * dy) as usize)).collect());
+                }
+            }
+        }
+    }
+
+    lines
+}
+
 impl BoardStructure<SubBoard> {
     pub fn new() -> BoardStructure<SubBoard> {
+        <BoardStructure<SubBoard>>::with_size(3)
+    }
+
+    pub fn with_size(size: usize) -> BoardStructure<SubBoard> {
+        assert!((1..=MAX_SIZE).contains(&size), "board size {} is not supported", size);
+
         BoardStructure {
-            items: [SubBoard::new(); 9]
+            items: [SubBoard::with_size(size); MAX_ITEMS],
+            size: size as u8,
         }
     }
 }
 
+impl Default for BoardStructure<SubBoard> {
+    fn default() -> BoardStructure<SubBoard> {
+        <BoardStructure<SubBoard>>::new()
+    }
+}
+
 impl BoardStructure<Square> {
     pub fn new() -> BoardStructure<Square> {
+        <BoardStructure<Square>>::with_size(3)
+    }
+
+    pub fn with_size(size: usize) -> BoardStructure<Square> {
+        assert!((1..=MAX_SIZE).contains(&size), "board size {} is not supported", size);
+
         BoardStructure {
-            items: [Square::new(); 9]
+            items: [Square::new(); MAX_ITEMS],
+            size: size as u8,
         }
     }
 }
 
-#[derive(Copy, Clone)]
+impl Default for BoardStructure<Square> {
+    fn default() -> BoardStructure<Square> {
+        <BoardStructure<Square>>::new()
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
 pub struct Square {
     result: Option<GameResult>,
 }
@@ -181,6 +339,12 @@ impl Square {
     }
 }
 
+impl Default for Square {
+    fn default() -> Square {
+        Square::new()
+    }
+}
+
 impl Owned for Square {
     fn result(&self) -> Option<GameResult> {
         self.result
@@ -1,6 +1,8 @@
+use std::fmt;
+
 use crate::game::player::Player;
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
 pub enum GameResult {
     Player1Wins,
     Player2Wins,
@@ -8,14 +10,9 @@ pub enum GameResult {
 }
 
 impl GameResult {
-    pub fn to_string(self) -> String {
-        match self {
-            GameResult::Player1Wins => "Player 1 wins!".to_string(),
-            GameResult::Player2Wins => "Player 2 wins!".to_string(),
-            GameResult::Draw        => "Draw!".to_string(),
-        }
-    }
-
+    /// The score for `player`: 1 for a win, 0.5 for a draw and 0 for a loss.
+    /// Results already name the actual winner under the rules' win condition,
+    /// so this holds for every variant.
     pub fn score(self, player: Player) -> f32 {
         match (self, player) {
             (GameResult::Player1Wins, Player::Player1) => 1.,
@@ -26,3 +23,13 @@ impl GameResult {
         }
     }
 }
+
+impl fmt::Display for GameResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GameResult::Player1Wins => write!(f, "Player 1 wins!"),
+            GameResult::Player2Wins => write!(f, "Player 2 wins!"),
+            GameResult::Draw        => write!(f, "Draw!"),
+        }
+    }
+}
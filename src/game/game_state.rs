@@ -2,25 +2,32 @@ use super::board::{Board, Owned};
 use super::player::Player;
 use super::action::Action;
 use crate::game::game_result::GameResult;
+use crate::game::rules::{ClosedBoardRule, Rules, WinCondition};
 
 use rand::seq::SliceRandom;
 use rand::Rng;
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct GameState {
     pub board: Board,
     pub current_player: Player,
     pub current_sub_x: Option<usize>,
     pub current_sub_y: Option<usize>,
+    pub rules: Rules,
 }
 
 impl GameState {
     pub fn new() -> GameState {
+        GameState::with_rules(Rules::new())
+    }
+
+    pub fn with_rules(rules: Rules) -> GameState {
         GameState {
-            board: Board::new(),
+            board: Board::with_size(rules.size),
             current_player: Player::Player1,
             current_sub_x: None,
             current_sub_y: None,
+            rules,
         }
     }
 
@@ -36,14 +43,39 @@ impl GameState {
         self.current_player
     }
 
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    /// Whether the game is over: the macro board is decided or no move is
+    /// left. Moves report the result as they are made, so this is for
+    /// positions that are set up rather than played to.
+    pub fn is_over(&self) -> bool {
+        let structure = self.board.structure();
+        let decided = match self.rules.win_condition {
+            WinCondition::MostSubBoards => structure.items().iter().all(|item| item.result().is_some()),
+            _ => structure.full_result(&self.rules).is_some(),
+        };
+
+        decided || self.possible_actions().is_empty()
+    }
+
+    /// The squares nobody has played in, closed sub-boards included.
+    pub fn empty_squares(&self) -> usize {
+        self.board.structure().items().iter()
+            .map(|sub_board| sub_board.structure().items().iter().filter(|square| square.result().is_none()).count())
+            .sum()
+    }
+
     pub fn make_move(&mut self, sub_x: usize, sub_y: usize, x: usize, y: usize) -> Option<GameResult> {
         let current_player = self.current_player();
 
-        let (new_x, new_y, result) = self.board_mut().make_move(Some(current_player.wins()),
-                                                                sub_x,
-                                                                sub_y,
-                                                                x.clone(),
-                                                                y.clone());
+        let (new_x, new_y, result) = self.board.make_move(Some(current_player.wins()),
+                                                          sub_x,
+                                                          sub_y,
+                                                          x,
+                                                          y,
+                                                          &self.rules);
 
         self.current_sub_x = new_x;
         self.current_sub_y = new_y;
@@ -52,176 +84,122 @@ impl GameState {
         result
     }
 
+    /// Counts the positions reachable in exactly `depth` moves, not counting
+    /// games that end earlier. Used to check move generation.
+    pub fn perft(&self, depth: usize) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+
+        let actions = self.possible_actions();
+        if depth == 1 {
+            return actions.len() as u64;
+        }
+
+        actions.iter().map(|action| {
+            let mut new_game_state = self.clone();
+            match action.apply(&mut new_game_state) {
+                Some(_) => 0,
+                None => new_game_state.perft(depth - 1),
+            }
+        }).sum()
+    }
+
     pub fn play_randomly(&self) -> GameResult {
+        let (_, result) = self.play_out(&mut rand::thread_rng());
+        result
+    }
+
+    /// Plays random moves until the game ends, returning the final position
+    /// along with the result.
+    pub fn play_out<R: Rng>(&self, rng: &mut R) -> (GameState, GameResult) {
         let mut actions = self.initialize_actions();
         let mut new_game_state = self.clone();
+        let size = self.board.size();
 
         loop {
             let action = match new_game_state.current_sub_x {
                 None => {
                     let possible_actions = new_game_state.possible_actions();
-                    let action = possible_actions.choose(&mut rand::thread_rng()).unwrap();
-                    actions[3 * action.sub_y + action.sub_x].retain(|ac| ac.x != action.x || ac.y != action.y);
+                    let action = possible_actions.choose(rng).unwrap();
+                    actions[size * action.sub_y + action.sub_x].retain(|ac| ac.x != action.x || ac.y != action.y);
                     action.clone()
                 },
                 Some(sub_x) => {
                     let sub_y = new_game_state.current_sub_y.unwrap();
-                    let random_num = rand::thread_rng().gen_range(0, actions[3 * sub_y + sub_x].len());
-                    actions[3 * sub_y + sub_x].remove(random_num).clone()
+                    let random_num = rng.gen_range(0, actions[size * sub_y + sub_x].len());
+                    actions[size * sub_y + sub_x].remove(random_num).clone()
                 },
             };
 
             let action_result = action.apply(&mut new_game_state);
             if let Some(result) = action_result {
-                return result;
+                return (new_game_state, result);
             }
         }
     }
 
-    pub fn initialize_actions(&self) -> [Vec<Action>; 9] {
-        [
-            (0..9).filter_map(|i| {
-                match self.board.structure().items[0].structure().items[i].result() {
-                    None => Some(Action::new(
-                        0,
-                        0,
-                        i % 3,
-                        i / 3,
-                        false,
-                    )),
-                    Some(_) => None,
-                }
-            }).collect(),
-            (0..9).filter_map(|i| {
-                match self.board.structure().items[1].structure().items[i].result() {
-                    None => Some(Action::new(
-                        1,
-                        0,
-                        i % 3,
-                        i / 3,
-                        false,
-                    )),
-                    Some(_) => None,
-                }
-            }).collect(),
-            (0..9).filter_map(|i| {
-                match self.board.structure().items[2].structure().items[i].result() {
-                    None => Some(Action::new(
-                        2,
-                        0,
-                        i % 3,
-                        i / 3,
-                        false,
-                    )),
-                    Some(_) => None,
-                }
-            }).collect(),
-            (0..9).filter_map(|i| {
-                match self.board.structure().items[3].structure().items[i].result() {
-                    None => Some(Action::new(
-                        0,
-                        1,
-                        i % 3,
-                        i / 3,
-                        false,
-                    )),
-                    Some(_) => None,
-                }
-            }).collect(),
-            (0..9).filter_map(|i| {
-                match self.board.structure().items[4].structure().items[i].result() {
-                    None => Some(Action::new(
-                        1,
-                        1,
-                        i % 3,
-                        i / 3,
-                        false,
-                    )),
-                    Some(_) => None,
-                }
-            }).collect(),
-            (0..9).filter_map(|i| {
-                match self.board.structure().items[5].structure().items[i].result() {
-                    None => Some(Action::new(
-                        2,
-                        1,
-                        i % 3,
-                        i / 3,
-                        false,
-                    )),
-                    Some(_) => None,
-                }
-            }).collect(),
-            (0..9).filter_map(|i| {
-                match self.board.structure().items[6].structure().items[i].result() {
-                    None => Some(Action::new(
-                        0,
-                        2,
-                        i % 3,
-                        i / 3,
-                        false,
-                    )),
-                    Some(_) => None,
-                }
-            }).collect(),
-            (0..9).filter_map(|i| {
-                match self.board.structure().items[7].structure().items[i].result() {
-                    None => Some(Action::new(
-                        1,
-                        2,
-                        i % 3,
-                        i / 3,
-                        false,
-                    )),
-                    Some(_) => None,
-                }
-            }).collect(),
-            (0..9).filter_map(|i| {
-                match self.board.structure().items[8].structure().items[i].result() {
-                    None => Some(Action::new(
-                        2,
-                        2,
-                        i % 3,
-                        i / 3,
-                        false,
-                    )),
-                    Some(_) => None,
+    /// The empty squares of every sub-board, indexed by `size * sub_y + sub_x`.
+    pub fn initialize_actions(&self) -> Vec<Vec<Action>> {
+        let size = self.board.size();
+        let mut actions = Vec::with_capacity(size * size);
+
+        for sub_y in 0..size {
+            for sub_x in 0..size {
+                let sub_board = self.board.get(sub_x, sub_y);
+                let mut sub_board_actions = Vec::with_capacity(size * size);
+
+                for y in 0..size {
+                    for x in 0..size {
+                        if sub_board.get(x, y).result().is_none() {
+                            sub_board_actions.push(Action::new(sub_x, sub_y, x, y, false));
+                        }
+                    }
                 }
-            }).collect(),
-        ]
+
+                actions.push(sub_board_actions);
+            }
+        }
+
+        actions
     }
 
     pub fn possible_actions(&self) -> Vec<Action> {
+        let size = self.board.size();
+
         match self.current_sub_x {
             Some(sub_x) => {
                 let sub_y = self.current_sub_y.unwrap();
-                let sub_board_items = &self.board().get(sub_x, sub_y).structure().items;
-                let mut vec = Vec::with_capacity(9);
+                let sub_board = self.board().get(sub_x, sub_y);
+                let mut vec = Vec::with_capacity(size * size);
 
-                for i in 0..9 {
-                    if sub_board_items[i].result().is_none() {
-                        vec.push(Action::new(sub_x, sub_y, i % 3, i / 3, false));
+                for y in 0..size {
+                    for x in 0..size {
+                        if sub_board.get(x, y).result().is_none() {
+                            vec.push(Action::new(sub_x, sub_y, x, y, false));
+                        }
                     }
                 }
 
                 vec
             },
             None => {
-                let mut vec = Vec::with_capacity(81);
-
-                let board_items = &self.board().structure().items;
-                for i in 0..9 {
-                    let sub_board = &board_items[i];
-
-                    if sub_board.result().is_some() {
-                        continue;
-                    }
+                let mut vec = Vec::with_capacity(size * size * size * size);
+
+                let closed_boards_playable = self.rules.closed_board == ClosedBoardRule::AnyEmptySquare;
+                for sub_y in 0..size {
+                    for sub_x in 0..size {
+                        let sub_board = self.board().get(sub_x, sub_y);
+                        if sub_board.result().is_some() && !closed_boards_playable {
+                            continue;
+                        }
 
-                    let sub_board_items = &sub_board.structure().items;
-                    let (sub_x, sub_y) = (i % 3, i / 3);
-                    for j in 0..9 {
-                        if sub_board_items[j].result().is_none() {
-                            vec.push(Action::new(sub_x, sub_y, j % 3, j / 3, true));
+                        for y in 0..size {
+                            for x in 0..size {
+                                if sub_board.get(x, y).result().is_none() {
+                                    vec.push(Action::new(sub_x, sub_y, x, y, true));
+                                }
+                            }
                         }
                     }
                 }
@@ -231,3 +209,9 @@ impl GameState {
         }
     }
 }
+
+impl Default for GameState {
+    fn default() -> GameState {
+        GameState::new()
+    }
+}
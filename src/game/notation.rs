@@ -0,0 +1,232 @@
+use crate::game::action::Action;
+use crate::game::board::{Owned, MAX_SIZE};
+use crate::game::game_result::GameResult;
+use crate::game::game_state::GameState;
+use crate::game::player::Player;
+use crate::game::rules::{ClosedBoardRule, Rules, WinCondition};
+
+/// Writes a position as the rows of the full grid, top to bottom and
+/// separated by `/`, using `O` for player 1, `X` for player 2 and `.` for empty
+/// squares, followed by the player to move (`1` or `2`) and the index
+/// `size * sub_y + sub_x` of the sub-board that has to be played in, or `-` if
+/// the player may play anywhere. The board size follows from the number of
+/// rows; if the line length differs from the size it is appended as a fourth
+/// field.
+///
+/// The starting position is `........./........./........./........./........./........./........./........./......... 1 -`.
+pub fn to_notation(game_state: &GameState) -> String {
+    let size = game_state.board().size();
+    let mut rows = Vec::with_capacity(size * size);
+
+    for sub_y in 0..size {
+        for y in 0..size {
+            let mut row = String::with_capacity(size * size);
+
+            for sub_x in 0..size {
+                for x in 0..size {
+                    row.push(match game_state.board().get(sub_x, sub_y).get(x, y).result() {
+                        Some(GameResult::Player1Wins) => 'O',
+                        Some(GameResult::Player2Wins) => 'X',
+                        _ => '.',
+                    });
+                }
+            }
+
+            rows.push(row);
+        }
+    }
+
+    let sub_board = match (game_state.current_sub_x, game_state.current_sub_y) {
+        (Some(sub_x), Some(sub_y)) => (size * sub_y + sub_x).to_string(),
+        _ => "-".to_string(),
+    };
+
+    let mut notation = format!("{} {} {}", rows.join("/"), game_state.current_player().num(), sub_board);
+    if game_state.rules().win_length != size {
+        notation.push_str(&format!(" {}", game_state.rules().win_length));
+    }
+
+    notation
+}
+
+pub fn from_notation(notation: &str) -> Result<GameState, String> {
+    let fields: Vec<&str> = notation.split_whitespace().collect();
+    if fields.len() != 3 && fields.len() != 4 {
+        return Err(format!("expected 3 or 4 fields, found {}", fields.len()));
+    }
+
+    let rows: Vec<&str> = fields[0].split('/').collect();
+    let size = (1..=MAX_SIZE)
+        .find(|size| size * size == rows.len())
+        .ok_or_else(|| format!("unsupported number of rows: {}", rows.len()))?;
+
+    let win_length = match fields.get(3) {
+        None => size,
+        Some(field) => field.parse::<usize>()
+            .ok()
+            .filter(|win_length| (1..=size).contains(win_length))
+            .ok_or_else(|| format!("invalid line length '{}'", field))?,
+    };
+
+    let mut game_state = GameState::with_rules(Rules::new().with_size(size, win_length));
+
+    for (row_index, row) in rows.iter().enumerate() {
+        if row.chars().count() != size * size {
+            return Err(format!("row {} does not have {} squares", row_index, size * size));
+        }
+
+        for (column_index, square) in row.chars().enumerate() {
+            let result = match square {
+                'O' => Some(GameResult::Player1Wins),
+                'X' => Some(GameResult::Player2Wins),
+                '.' => None,
+                other => return Err(format!("invalid square '{}'", other)),
+            };
+
+            game_state.board_mut()
+                .get_mut(column_index / size, row_index / size)
+                .structure_mut()
+                .set_result_at(column_index % size, row_index % size, result);
+        }
+    }
+
+    let rules = *game_state.rules();
+    for sub_y in 0..size {
+        for sub_x in 0..size {
+            let sub_board = game_state.board_mut().get_mut(sub_x, sub_y);
+            let result = sub_board.structure().full_result(&rules);
+            sub_board.set_result(result);
+        }
+    }
+
+    game_state.current_player = match fields[1] {
+        "1" => Player::Player1,
+        "2" => Player::Player2,
+        other => return Err(format!("invalid player '{}'", other)),
+    };
+
+    match fields[2] {
+        "-" => {},
+        index => {
+            let index = index.parse::<usize>()
+                .ok()
+                .filter(|&index| index < size * size)
+                .ok_or_else(|| format!("invalid sub-board '{}'", index))?;
+
+            if game_state.board().get(index % size, index / size).result().is_some() {
+                return Err(format!("sub-board {} is already closed", index));
+            }
+
+            game_state.current_sub_x = Some(index % size);
+            game_state.current_sub_y = Some(index / size);
+        },
+    }
+
+    Ok(game_state)
+}
+
+/// Writes a move as the four digits a player types to make it: the sub-board's
+/// x and y, then the square's x and y within it. `1102` is the bottom-middle
+/// square of the centre sub-board.
+pub fn action_to_notation(action: &Action) -> String {
+    format!("{}{}{}{}", action.sub_x, action.sub_y, action.x, action.y)
+}
+
+/// Reads a move in the notation of `action_to_notation`, checking that it is
+/// legal in the given position.
+pub fn action_from_notation(notation: &str, game_state: &GameState) -> Result<Action, String> {
+    let digits: Vec<usize> = notation.chars()
+        .map(|digit| digit.to_digit(10).map(|digit| digit as usize))
+        .collect::<Option<Vec<usize>>>()
+        .filter(|digits| digits.len() == 4)
+        .ok_or_else(|| format!("invalid move '{}'", notation))?;
+
+    game_state.possible_actions()
+        .into_iter()
+        .find(|action| (action.sub_x, action.sub_y, action.x, action.y) == (digits[0], digits[1], digits[2], digits[3]))
+        .ok_or_else(|| format!("illegal move '{}'", notation))
+}
+
+/// Writes a result as `1` or `2` for a win of that player, or `=` for a draw.
+pub fn result_to_notation(result: GameResult) -> &'static str {
+    match result {
+        GameResult::Player1Wins => "1",
+        GameResult::Player2Wins => "2",
+        GameResult::Draw        => "=",
+    }
+}
+
+pub fn result_from_notation(notation: &str) -> Result<GameResult, String> {
+    match notation {
+        "1" => Ok(GameResult::Player1Wins),
+        "2" => Ok(GameResult::Player2Wins),
+        "=" => Ok(GameResult::Draw),
+        other => Err(format!("invalid result '{}'", other)),
+    }
+}
+
+/// Writes the rule variants a position does not carry itself: where players
+/// may go when sent to a closed sub-board (`open` or `any`), the win condition
+/// (`standard`, `misere` or `most`), and the flags `draws-count-for-both` and
+/// `sub-board-tiebreak` when they are set. The standard rules are
+/// `open standard`.
+pub fn rules_to_notation(rules: &Rules) -> String {
+    let mut fields = vec![
+        match rules.closed_board {
+            ClosedBoardRule::AnyOpenSubBoard => "open",
+            ClosedBoardRule::AnyEmptySquare => "any",
+        },
+        match rules.win_condition {
+            WinCondition::Standard => "standard",
+            WinCondition::Misere => "misere",
+            WinCondition::MostSubBoards => "most",
+        },
+    ];
+
+    if rules.draws_count_for_both {
+        fields.push("draws-count-for-both");
+    }
+    if rules.sub_board_tiebreak {
+        fields.push("sub-board-tiebreak");
+    }
+
+    fields.join(" ")
+}
+
+/// Applies rules in the notation of `rules_to_notation` on top of `rules`,
+/// which supplies the board size.
+pub fn rules_from_notation(notation: &str, rules: Rules) -> Result<Rules, String> {
+    let fields: Vec<&str> = notation.split_whitespace().collect();
+    if fields.len() < 2 {
+        return Err(format!("expected at least 2 fields, found {}", fields.len()));
+    }
+
+    let closed_board = match fields[0] {
+        "open" => ClosedBoardRule::AnyOpenSubBoard,
+        "any" => ClosedBoardRule::AnyEmptySquare,
+        other => return Err(format!("invalid closed board rule '{}'", other)),
+    };
+
+    let win_condition = match fields[1] {
+        "standard" => WinCondition::Standard,
+        "misere" => WinCondition::Misere,
+        "most" => WinCondition::MostSubBoards,
+        other => return Err(format!("invalid win condition '{}'", other)),
+    };
+
+    let mut rules = rules
+        .with_closed_board(closed_board)
+        .with_win_condition(win_condition)
+        .with_draws_count_for_both(false)
+        .with_sub_board_tiebreak(false);
+
+    for &flag in fields[2..].iter() {
+        rules = match flag {
+            "draws-count-for-both" => rules.with_draws_count_for_both(true),
+            "sub-board-tiebreak" => rules.with_sub_board_tiebreak(true),
+            other => return Err(format!("invalid rule '{}'", other)),
+        };
+    }
+
+    Ok(rules)
+}
@@ -1,6 +1,6 @@
 use crate::game::game_result::GameResult;
 
-#[derive(Copy, Clone, PartialEq, Eq)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
 pub enum Player {
     Player1,
     Player2,
@@ -19,3 +19,25 @@ impl Player {
         if self == Player::Player1 { GameResult::Player1Wins } else { GameResult::Player2Wins }
     }
 }
+
+/// How a player is named and shown on the board.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct PlayerConfig {
+    pub name: String,
+    pub symbol: char,
+}
+
+impl PlayerConfig {
+    pub fn new(name: &str, symbol: char) -> PlayerConfig {
+        PlayerConfig { name: name.to_string(), symbol }
+    }
+
+    /// The configuration used when nothing else is given: "Player 1" as `O`
+    /// and "Player 2" as `X`.
+    pub fn default_for(player: Player) -> PlayerConfig {
+        match player {
+            Player::Player1 => PlayerConfig::new("Player 1", 'O'),
+            Player::Player2 => PlayerConfig::new("Player 2", 'X'),
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::game::action::Action;
+use crate::game::game_result::GameResult;
+use crate::game::game_state::GameState;
+use crate::game::notation::{action_from_notation, action_to_notation, from_notation, result_from_notation,
+                            result_to_notation, rules_from_notation, rules_to_notation, to_notation};
+use crate::game::player::{Player, PlayerConfig};
+
+/// A played game: who played which side, the starting position and every move
+/// made from it.
+#[derive(Clone)]
+pub struct GameRecord {
+    /// Indexed by `Player::num() - 1`.
+    pub players: [PlayerConfig; 2],
+    pub initial_state: GameState,
+    pub moves: Vec<Action>,
+    pub result: Option<GameResult>,
+}
+
+impl GameRecord {
+    pub fn new(initial_state: GameState) -> GameRecord {
+        GameRecord {
+            players: [PlayerConfig::default_for(Player::Player1), PlayerConfig::default_for(Player::Player2)],
+            initial_state,
+            moves: Vec::new(),
+            result: None,
+        }
+    }
+
+    pub fn with_players(mut self, player1: PlayerConfig, player2: PlayerConfig) -> GameRecord {
+        self.players = [player1, player2];
+        self
+    }
+
+    pub fn player(&self, player: Player) -> &PlayerConfig {
+        &self.players[player.num() - 1]
+    }
+
+    /// Records a move along with the result it led to, if any.
+    pub fn push(&mut self, action: Action, result: Option<GameResult>) {
+        self.moves.push(action);
+        self.result = result;
+    }
+
+    /// Takes back the last move, and with it any result.
+    pub fn pop(&mut self) -> Option<Action> {
+        self.result = None;
+        self.moves.pop()
+    }
+
+    /// Ends the game in a win for the opponent of `player`.
+    pub fn resign(&mut self, player: Player) {
+        self.result = Some(player.next().wins());
+    }
+
+    /// Ends the game in a draw the players agreed to.
+    pub fn agree_draw(&mut self) {
+        self.result = Some(GameResult::Draw);
+    }
+
+    /// The position after every recorded move.
+    pub fn final_state(&self) -> GameState {
+        let mut game_state = self.initial_state.clone();
+        for action in self.moves.iter() {
+            action.apply(&mut game_state);
+        }
+        game_state
+    }
+
+    /// The result spelled out with the players' names.
+    pub fn describe_result(&self) -> String {
+        match self.result {
+            Some(GameResult::Player1Wins) => format!("{} wins!", self.player(Player::Player1).name),
+            Some(GameResult::Player2Wins) => format!("{} wins!", self.player(Player::Player2).name),
+            Some(GameResult::Draw) => "Draw!".to_string(),
+            None => "Unfinished".to_string(),
+        }
+    }
+
+    /// Writes the record as text, one field per line:
+    ///
+    /// ```text
+    /// player1 O Player 1
+    /// player2 X Player 2
+    /// start ........./........./........./........./........./........./........./........./......... 1 -
+    /// rules open standard
+    /// moves 1111 1101
+    /// result *
+    /// ```
+    ///
+    /// Players are given by their symbol and name, the start in position
+    /// notation, the rules in rules notation and the moves in move notation.
+    /// The result is `*` while the game is unfinished.
+    pub fn to_text(&self) -> String {
+        let moves: Vec<String> = self.moves.iter().map(action_to_notation).collect();
+
+        format!(
+            "player1 {} {}\nplayer2 {} {}\nstart {}\nrules {}\nmoves {}\nresult {}\n",
+            self.players[0].symbol, self.players[0].name,
+            self.players[1].symbol, self.players[1].name,
+            to_notation(&self.initial_state),
+            rules_to_notation(self.initial_state.rules()),
+            moves.join(" "),
+            self.result.map_or("*", result_to_notation),
+        )
+    }
+
+    /// Reads a record written by `to_text`, replaying the moves to check that
+    /// they are legal and lead to the given result. A game whose moves do not
+    /// reach a result may still have one, from a resignation.
+    pub fn from_text(text: &str) -> Result<GameRecord, String> {
+        let mut fields = HashMap::new();
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            let (key, value) = match line.find(' ') {
+                Some(index) => (&line[..index], &line[index + 1..]),
+                None => (line, ""),
+            };
+            fields.insert(key, value);
+        }
+
+        let field = |key: &str| fields.get(key).cloned().ok_or_else(|| format!("missing field '{}'", key));
+        let player = |key: &str| -> Result<PlayerConfig, String> {
+            let value = field(key)?;
+            let mut chars = value.chars();
+            match (chars.next(), chars.next()) {
+                (Some(symbol), Some(' ')) => Ok(PlayerConfig::new(chars.as_str(), symbol)),
+                _ => Err(format!("invalid player '{}'", value)),
+            }
+        };
+
+        let mut initial_state = from_notation(field("start")?)?;
+        initial_state.rules = rules_from_notation(field("rules")?, initial_state.rules)?;
+
+        let mut record = GameRecord::new(initial_state.clone()).with_players(player("player1")?, player("player2")?);
+        let mut game_state = initial_state;
+        for notation in field("moves")?.split_whitespace() {
+            if record.result.is_some() {
+                return Err("moves after the end of the game".to_string());
+            }
+
+            let action = action_from_notation(notation, &game_state)?;
+            let result = action.apply(&mut game_state);
+            record.push(action, result);
+        }
+
+        let result = match field("result")? {
+            "*" => None,
+            result => Some(result_from_notation(result)?),
+        };
+        if record.result.is_some() && result != record.result {
+            return Err("the result does not match the moves".to_string());
+        }
+        record.result = result;
+
+        Ok(record)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<GameRecord> {
+        let text = fs::read_to_string(path)?;
+        GameRecord::from_text(&text).map_err(|message| io::Error::new(io::ErrorKind::InvalidData, message))
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_text())
+    }
+}
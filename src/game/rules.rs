@@ -0,0 +1,184 @@
+use crate::game::board::{BoardStructure, Owned, SubBoard, MAX_SIZE};
+use crate::game::game_result::GameResult;
+
+/// Where a player may move after being sent to a sub-board that is already
+/// closed.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum ClosedBoardRule {
+    /// Any empty square of a sub-board that is still open.
+    AnyOpenSubBoard,
+    /// Any empty square, even in closed sub-boards. Moves there do not change
+    /// the closed sub-board's result, but do decide where the opponent goes.
+    AnyEmptySquare,
+}
+
+/// How the macro board decides the game.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum WinCondition {
+    /// Completing a line of sub-boards wins.
+    Standard,
+    /// Completing a line of sub-boards loses. Tiebreaks are reversed as well,
+    /// so the player with fewer sub-boards wins them.
+    Misere,
+    /// Lines do not count; once every sub-board is closed, the player who won
+    /// more of them wins.
+    MostSubBoards,
+}
+
+/// Rule variants the game engine honours. `Rules::new()` gives the standard
+/// rules.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Rules {
+    /// The macro board is `size` by `size` sub-boards of `size` by `size`
+    /// squares.
+    pub size: usize,
+    /// How many squares or sub-boards in a row a player needs.
+    pub win_length: usize,
+    pub closed_board: ClosedBoardRule,
+    pub win_condition: WinCondition,
+    /// Drawn sub-boards count for both players when completing a line on the
+    /// macro board. A line of nothing but drawn sub-boards counts for neither.
+    pub draws_count_for_both: bool,
+    /// When every sub-board is closed without a line, the player who won more
+    /// sub-boards wins the game.
+    pub sub_board_tiebreak: bool,
+}
+
+impl Rules {
+    pub fn new() -> Rules {
+        Rules {
+            size: 3,
+            win_length: 3,
+            closed_board: ClosedBoardRule::AnyOpenSubBoard,
+            win_condition: WinCondition::Standard,
+            draws_count_for_both: false,
+            sub_board_tiebreak: false,
+        }
+    }
+
+    pub fn with_size(mut self, size: usize, win_length: usize) -> Rules {
+        assert!((1..=MAX_SIZE).contains(&size), "board size {} is not supported", size);
+        assert!((1..=size).contains(&win_length), "cannot get {} in a row on a board of size {}", win_length, size);
+
+        self.size = size;
+        self.win_length = win_length;
+        self
+    }
+
+    pub fn is_standard_size(&self) -> bool {
+        self.size == 3 && self.win_length == 3
+    }
+
+    pub fn with_closed_board(mut self, closed_board: ClosedBoardRule) -> Rules {
+        self.closed_board = closed_board;
+        self
+    }
+
+    pub fn with_win_condition(mut self, win_condition: WinCondition) -> Rules {
+        self.win_condition = win_condition;
+        self
+    }
+
+    pub fn with_draws_count_for_both(mut self, draws_count_for_both: bool) -> Rules {
+        self.draws_count_for_both = draws_count_for_both;
+        self
+    }
+
+    pub fn with_sub_board_tiebreak(mut self, sub_board_tiebreak: bool) -> Rules {
+        self.sub_board_tiebreak = sub_board_tiebreak;
+        self
+    }
+
+    /// The player who owns a line of results, if any.
+    #[inline]
+    pub fn line_owner(&self, results: &[Option<GameResult>]) -> Option<GameResult> {
+        let first = results[0];
+        if first.is_some() && first != Some(GameResult::Draw) && results.iter().all(|&result| result == first) {
+            return first;
+        }
+
+        if self.draws_count_for_both {
+            for &player in [GameResult::Player1Wins, GameResult::Player2Wins].iter() {
+                let counts = |&result: &Option<GameResult>| result == Some(player) || result == Some(GameResult::Draw);
+
+                if results.iter().all(counts) && results.contains(&Some(player)) {
+                    return Some(player);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// The result of the game after the sub-board at `last_x`, `last_y` of
+    /// the macro board was closed, if the game is over. The result names the
+    /// actual winner under the win condition, so `GameResult::score` needs no
+    /// further adjustment.
+    pub fn game_result(&self, structure: &BoardStructure<SubBoard>, last_x: usize, last_y: usize) -> Option<GameResult> {
+        match self.win_condition {
+            WinCondition::Standard => structure.check_result(last_x, last_y, self).map(|result| match result {
+                GameResult::Draw => self.settle_draw(structure),
+                result => result,
+            }),
+            WinCondition::Misere => structure.check_result(last_x, last_y, self).map(|result| match result {
+                GameResult::Draw => reverse(self.settle_draw(structure)),
+                result => reverse(result),
+            }),
+            WinCondition::MostSubBoards => {
+                if structure.items().iter().any(|item| item.result().is_none()) {
+                    None
+                } else {
+                    Some(sub_board_majority(structure))
+                }
+            },
+        }
+    }
+
+    /// Settles a drawn macro board. Only a board on which every sub-board was
+    /// closed without a line goes to the tiebreak; lines for both players at
+    /// once stay a draw.
+    fn settle_draw(&self, structure: &BoardStructure<SubBoard>) -> GameResult {
+        if structure.full_result(self) == Some(GameResult::Draw) {
+            self.resolve_draw(structure)
+        } else {
+            GameResult::Draw
+        }
+    }
+
+    /// Settles a game in which every sub-board was closed without a line.
+    pub fn resolve_draw(&self, structure: &BoardStructure<SubBoard>) -> GameResult {
+        if !self.sub_board_tiebreak {
+            return GameResult::Draw;
+        }
+
+        sub_board_majority(structure)
+    }
+}
+
+fn sub_board_majority(structure: &BoardStructure<SubBoard>) -> GameResult {
+    let count = |player: GameResult| structure.items().iter().filter(|item| item.result() == Some(player)).count();
+    let player1 = count(GameResult::Player1Wins);
+    let player2 = count(GameResult::Player2Wins);
+
+    if player1 > player2 {
+        GameResult::Player1Wins
+    } else if player2 > player1 {
+        GameResult::Player2Wins
+    } else {
+        GameResult::Draw
+    }
+}
+
+fn reverse(result: GameResult) -> GameResult {
+    match result {
+        GameResult::Player1Wins => GameResult::Player2Wins,
+        GameResult::Player2Wins => GameResult::Player1Wins,
+        GameResult::Draw => GameResult::Draw,
+    }
+}
+
+impl Default for Rules {
+    fn default() -> Rules {
+        Rules::new()
+    }
+}
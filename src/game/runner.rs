@@ -0,0 +1,345 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::actor::{Actor, ActorError, CancelToken, Clock, Context, Decision, SearchReport};
+use crate::game::action::Action;
+use crate::game::game_state::GameState;
+use crate::game::notation::action_to_notation;
+use crate::game::player::{Player, PlayerConfig};
+use crate::game::record::GameRecord;
+
+/// Which of the two actors passed to `Match::play` moves first.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Starter {
+    First,
+    Second,
+    Random,
+}
+
+/// Everything about a game besides the actors: how the players are called and
+/// shown, who starts and from which position.
+#[derive(Clone)]
+pub struct Setup {
+    /// Configurations for the first and second actor passed to `play`.
+    pub players: [PlayerConfig; 2],
+    pub starter: Starter,
+    pub initial_state: GameState,
+}
+
+impl Setup {
+    pub fn new() -> Setup {
+        Setup {
+            players: [PlayerConfig::default_for(Player::Player1), PlayerConfig::default_for(Player::Player2)],
+            starter: Starter::First,
+            initial_state: GameState::new(),
+        }
+    }
+
+    pub fn with_players(mut self, first: PlayerConfig, second: PlayerConfig) -> Setup {
+        self.players = [first, second];
+        self
+    }
+
+    pub fn with_starter(mut self, starter: Starter) -> Setup {
+        self.starter = starter;
+        self
+    }
+
+    pub fn with_initial_state(mut self, initial_state: GameState) -> Setup {
+        self.initial_state = initial_state;
+        self
+    }
+}
+
+impl Default for Setup {
+    fn default() -> Setup {
+        Setup::new()
+    }
+}
+
+/// Follows a game run by `Match`: a renderer, a logger, a clock and so on.
+/// Every method is given the position and the record after what it reports.
+/// `gui::Printer` is the plain-text interface's observer; the full-screen
+/// interface has its own.
+pub trait GameObserver {
+    /// The players are in their seats; nobody has moved yet.
+    fn game_started(&mut self, _game_state: &GameState, _record: &GameRecord) {}
+
+    /// `player` is asked for a decision.
+    fn turn_started(&mut self, _player: Player, _game_state: &GameState, _record: &GameRecord) {}
+
+    /// The actor that is about to move searched for its move and found this.
+    fn search_reported(&mut self, _player: Player, _report: &SearchReport) {}
+
+    /// A move was made after `elapsed` spent deciding on it.
+    fn move_made(&mut self, _action: &Action, _elapsed: Duration, _game_state: &GameState, _record: &GameRecord) {}
+
+    /// The last move of the player to move and the reply to it were taken
+    /// back.
+    fn move_undone(&mut self, _game_state: &GameState, _record: &GameRecord) {}
+
+    /// `player` asked to undo before they had moved.
+    fn nothing_to_undo(&mut self, _player: Player) {}
+
+    fn resigned(&mut self, _player: Player, _record: &GameRecord) {}
+
+    /// `player` offered a draw, and the opponent accepted or declined.
+    fn draw_offered(&mut self, _player: Player, _accepted: bool, _record: &GameRecord) {}
+
+    /// `player`'s actor failed, which ends the game without a result.
+    fn actor_failed(&mut self, _player: Player, _error: &ActorError, _record: &GameRecord) {}
+
+    /// The game is over: with its result, or abandoned if the record has
+    /// none.
+    fn game_ended(&mut self, _game_state: &GameState, _record: &GameRecord) {}
+}
+
+impl<O: GameObserver + ?Sized> GameObserver for &mut O {
+    fn game_started(&mut self, game_state: &GameState, record: &GameRecord) {
+        (**self).game_started(game_state, record)
+    }
+
+    fn turn_started(&mut self, player: Player, game_state: &GameState, record: &GameRecord) {
+        (**self).turn_started(player, game_state, record)
+    }
+
+    fn search_reported(&mut self, player: Player, report: &SearchReport) {
+        (**self).search_reported(player, report)
+    }
+
+    fn move_made(&mut self, action: &Action, elapsed: Duration, game_state: &GameState, record: &GameRecord) {
+        (**self).move_made(action, elapsed, game_state, record)
+    }
+
+    fn move_undone(&mut self, game_state: &GameState, record: &GameRecord) {
+        (**self).move_undone(game_state, record)
+    }
+
+    fn nothing_to_undo(&mut self, player: Player) {
+        (**self).nothing_to_undo(player)
+    }
+
+    fn resigned(&mut self, player: Player, record: &GameRecord) {
+        (**self).resigned(player, record)
+    }
+
+    fn draw_offered(&mut self, player: Player, accepted: bool, record: &GameRecord) {
+        (**self).draw_offered(player, accepted, record)
+    }
+
+    fn actor_failed(&mut self, player: Player, error: &ActorError, record: &GameRecord) {
+        (**self).actor_failed(player, error, record)
+    }
+
+    fn game_ended(&mut self, game_state: &GameState, record: &GameRecord) {
+        (**self).game_ended(game_state, record)
+    }
+}
+
+/// How long past the time limit an actor may still answer, to allow for
+/// stopping a search and returning from it.
+pub const GRACE: Duration = Duration::from_millis(100);
+
+/// Drives games between any two actors, humans or engines, and tells any
+/// number of observers what happens: when the game starts, each move with the
+/// time taken over it, each engine's search report and the result. `Match`
+/// does no input or output of its own, so every front end, logger or clock
+/// shares the same game loop; the match command plays its games through it
+/// too.
+///
+/// On its turn an actor decides on the position given a `Context` holding the
+/// game so far, its clock and a cancellation token, which `with_time_limit`
+/// and `with_cancel` set. It may move, undo, resign, offer a draw or quit, or
+/// fail with an `ActorError` such as a disconnect or timeout, which ends the
+/// game without a result. Actors are also told when a game starts and which
+/// side they play, what their opponent played and when the game is over; the
+/// MCTS engine uses this to search on from the part of its last tree the game
+/// went into.
+pub struct Match<'a> {
+    setup: Setup,
+    delay: Duration,
+    time_limit: Option<Duration>,
+    cancel: CancelToken,
+    observers: Vec<Box<dyn GameObserver + 'a>>,
+}
+
+impl<'a> Match<'a> {
+    pub fn new(setup: Setup) -> Match<'a> {
+        Match { setup, delay: Duration::from_millis(0), time_limit: None, cancel: CancelToken::new(), observers: Vec::new() }
+    }
+
+    /// Pauses after every move, so that engine games can be followed.
+    pub fn with_delay(mut self, delay: Duration) -> Match<'a> {
+        self.delay = delay;
+        self
+    }
+
+    /// Gives every actor this long for each decision. Actors see the clock in
+    /// their context; searching ones stop in time, others may give up with
+    /// `ActorError::Timeout`. A decision that still comes in more than
+    /// `GRACE` after the limit is treated as a timeout.
+    pub fn with_time_limit(mut self, time_limit: Duration) -> Match<'a> {
+        self.time_limit = Some(time_limit);
+        self
+    }
+
+    /// Lets the game be stopped from elsewhere, such as another thread. Once
+    /// the token is cancelled, the game ends without a result before the next
+    /// turn, and actors still thinking are asked to stop.
+    pub fn with_cancel(mut self, cancel: CancelToken) -> Match<'a> {
+        self.cancel = cancel;
+        self
+    }
+
+    /// Adds an observer. Pass `&mut observer` to keep it once the match is
+    /// over.
+    pub fn with_observer<O: GameObserver + 'a>(mut self, observer: O) -> Match<'a> {
+        self.observers.push(Box::new(observer));
+        self
+    }
+
+    fn notify<F: FnMut(&mut dyn GameObserver)>(&mut self, mut notify: F) {
+        for observer in self.observers.iter_mut() {
+            notify(observer.as_mut());
+        }
+    }
+
+    /// Decides whether the first actor plays player 1 and starts the record
+    /// with the players in their seats.
+    fn seat(&self) -> (bool, GameRecord) {
+        let setup = &self.setup;
+        let first_starts = match setup.starter {
+            Starter::First => true,
+            Starter::Second => false,
+            Starter::Random => rand::random(),
+        };
+        let first_is_player1 = first_starts == (setup.initial_state.current_player() == Player::Player1);
+
+        let [first, second] = setup.players.clone();
+        let (player1, player2) = if first_is_player1 { (first, second) } else { (second, first) };
+
+        (first_is_player1, GameRecord::new(setup.initial_state.clone()).with_players(player1, player2))
+    }
+
+    /// Turns a decision that came in too late into a timeout.
+    fn in_time<T>(&self, start: Instant, decision: Result<T, ActorError>) -> Result<T, ActorError> {
+        match self.time_limit {
+            Some(limit) if decision.is_ok() && start.elapsed() > limit + GRACE => Err(ActorError::Timeout),
+            _ => decision,
+        }
+    }
+
+    fn context<'r>(&self, record: &'r GameRecord) -> Context<'r> {
+        Context::new(record).with_clock(Clock::new(self.time_limit)).with_cancel(self.cancel.clone())
+    }
+
+    /// Plays a game. The starter decides which of the actors moves for the
+    /// player to move in the initial position. Actors may also take back
+    /// moves, resign or offer draws. Quitting, cancellation, failing actors
+    /// and illegal moves leave the game unfinished.
+    pub fn play(&mut self, first: &mut dyn Actor, second: &mut dyn Actor) -> GameRecord {
+        let (first_is_player1, mut record) = self.seat();
+        let mut actors: [&mut dyn Actor; 2] = if first_is_player1 { [first, second] } else { [second, first] };
+
+        let mut game_state = self.setup.initial_state.clone();
+        for (actor, player) in actors.iter_mut().zip([Player::Player1, Player::Player2].iter()) {
+            actor.new_game(&game_state, *player);
+        }
+        self.notify(|observer| observer.game_started(&game_state, &record));
+
+        self.run(&mut actors, &mut game_state, &mut record);
+
+        self.notify(|observer| observer.game_ended(&game_state, &record));
+        for actor in actors.iter_mut() {
+            actor.game_over(&record);
+        }
+        record
+    }
+
+    fn accept_draw(&self, actor: &mut dyn Actor, game_state: &GameState, record: &GameRecord) -> Result<bool, ActorError> {
+        let start = Instant::now();
+        let accepted = actor.accept_draw(game_state, &self.context(record));
+        self.in_time(start, accepted)
+    }
+
+    /// Asks the actors for decisions until the game is over or stopped.
+    fn run(&mut self, actors: &mut [&mut dyn Actor; 2], game_state: &mut GameState, record: &mut GameRecord) {
+        loop {
+            let current = game_state.current_player();
+            let (actor, opponent) = (current.num() - 1, current.next().num() - 1);
+            if self.cancel.is_cancelled() {
+                return;
+            }
+            self.notify(|observer| observer.turn_started(current, game_state, record));
+
+            let start = Instant::now();
+            let decision = actors[actor].get_decision(game_state, &self.context(record));
+            let elapsed = start.elapsed();
+            let decision = self.in_time(start, decision);
+
+            let decision = match decision {
+                Ok(decision) => decision,
+                Err(error) => {
+                    self.notify(|observer| observer.actor_failed(current, &error, record));
+                    return;
+                },
+            };
+
+            match decision {
+                Decision::Move(action) => {
+                    let square = |action: &Action| (action.sub_x, action.sub_y, action.x, action.y);
+                    let action = match game_state.possible_actions().into_iter().find(|legal| square(legal) == square(&action)) {
+                        Some(action) => action,
+                        None => {
+                            let error = ActorError::Failed(format!("illegal move {}", action_to_notation(&action)));
+                            self.notify(|observer| observer.actor_failed(current, &error, record));
+                            return;
+                        },
+                    };
+
+                    if let Some(report) = actors[actor].search_report() {
+                        self.notify(|observer| observer.search_reported(current, &report));
+                    }
+
+                    let result = action.apply(game_state);
+                    record.push(action.clone(), result);
+                    self.notify(|observer| observer.move_made(&action, elapsed, game_state, record));
+                    actors[opponent].opponent_moved(&action, game_state);
+
+                    if !self.delay.is_zero() {
+                        thread::sleep(self.delay);
+                    }
+                },
+                // Back to the same player's previous turn.
+                Decision::Undo if record.moves.len() < 2 => self.notify(|observer| observer.nothing_to_undo(current)),
+                Decision::Undo => {
+                    record.pop();
+                    record.pop();
+                    *game_state = record.final_state();
+                    self.notify(|observer| observer.move_undone(game_state, record));
+                },
+                Decision::Resign => {
+                    record.resign(current);
+                    self.notify(|observer| observer.resigned(current, record));
+                },
+                Decision::OfferDraw => match self.accept_draw(actors[opponent], game_state, record) {
+                    Ok(accepted) => {
+                        if accepted {
+                            record.agree_draw();
+                        }
+                        self.notify(|observer| observer.draw_offered(current, accepted, record));
+                    },
+                    Err(error) => {
+                        self.notify(|observer| observer.actor_failed(current.next(), &error, record));
+                        return;
+                    },
+                },
+                Decision::Quit => return,
+            }
+
+            if record.result.is_some() {
+                return;
+            }
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use crate::game::action::Action;
+use crate::game::board::Owned;
+use crate::game::game_state::GameState;
+use crate::game::notation::to_notation;
+
+/// The 8 symmetries of the square. Each acts on the macro board and on every
+/// sub-board at the same time, so it maps legal positions to legal positions
+/// with the same value.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Symmetry {
+    Identity,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    FlipHorizontal,
+    FlipVertical,
+    FlipDiagonal,
+    FlipAntiDiagonal,
+}
+
+pub const SYMMETRIES: [Symmetry; 8] = [
+    Symmetry::Identity,
+    Symmetry::Rotate90,
+    Symmetry::Rotate180,
+    Symmetry::Rotate270,
+    Symmetry::FlipHorizontal,
+    Symmetry::FlipVertical,
+    Symmetry::FlipDiagonal,
+    Symmetry::FlipAntiDiagonal,
+];
+
+impl Symmetry {
+    pub fn inverse(self) -> Symmetry {
+        match self {
+            Symmetry::Rotate90  => Symmetry::Rotate270,
+            Symmetry::Rotate270 => Symmetry::Rotate90,
+            other               => other,
+        }
+    }
+
+    /// Maps coordinates on a `size` by `size` board; rotations are clockwise
+    /// with y going down.
+    pub fn apply(self, x: usize, y: usize, size: usize) -> (usize, usize) {
+        let last = size - 1;
+
+        match self {
+            Symmetry::Identity         => (x, y),
+            Symmetry::Rotate90         => (last - y, x),
+            Symmetry::Rotate180        => (last - x, last - y),
+            Symmetry::Rotate270        => (y, last - x),
+            Symmetry::FlipHorizontal   => (last - x, y),
+            Symmetry::FlipVertical     => (x, last - y),
+            Symmetry::FlipDiagonal     => (y, x),
+            Symmetry::FlipAntiDiagonal => (last - y, last - x),
+        }
+    }
+
+    pub fn transform_action(self, action: &Action, size: usize) -> Action {
+        let (sub_x, sub_y) = self.apply(action.sub_x, action.sub_y, size);
+        let (x, y) = self.apply(action.x, action.y, size);
+
+        Action::new(sub_x, sub_y, x, y, action.full_board)
+    }
+
+    pub fn transform_state(self, game_state: &GameState) -> GameState {
+        let mut transformed = game_state.clone();
+        let size = game_state.board().size();
+
+        for sub_y in 0..size {
+            for sub_x in 0..size {
+                let sub_board = game_state.board().get(sub_x, sub_y);
+                let (new_sub_x, new_sub_y) = self.apply(sub_x, sub_y, size);
+                let new_sub_board = transformed.board_mut().get_mut(new_sub_x, new_sub_y);
+
+                new_sub_board.set_result(sub_board.result());
+                for y in 0..size {
+                    for x in 0..size {
+                        let (new_x, new_y) = self.apply(x, y, size);
+                        new_sub_board.structure_mut().set_result_at(new_x, new_y, sub_board.get(x, y).result());
+                    }
+                }
+            }
+        }
+
+        if let (Some(sub_x), Some(sub_y)) = (game_state.current_sub_x, game_state.current_sub_y) {
+            let (new_sub_x, new_sub_y) = self.apply(sub_x, sub_y, size);
+            transformed.current_sub_x = Some(new_sub_x);
+            transformed.current_sub_y = Some(new_sub_y);
+        }
+
+        transformed
+    }
+}
+
+/// Picks the representative of a position's symmetry class with the smallest
+/// notation, together with the symmetry that maps the position onto it. Moves
+/// in the canonical position map back with `symmetry.inverse()`.
+pub fn canonicalize(game_state: &GameState) -> (GameState, Symmetry) {
+    SYMMETRIES.iter()
+        .map(|&symmetry| {
+            let transformed = symmetry.transform_state(game_state);
+            (to_notation(&transformed), transformed, symmetry)
+        })
+        .min_by(|(a, _, _), (b, _, _)| a.cmp(b))
+        .map(|(_, transformed, symmetry)| (transformed, symmetry))
+        .unwrap()
+}
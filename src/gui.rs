@@ -1,112 +1,208 @@
-use std::io::stdin;
-
-use crate::actor::Actor;
-use crate::game::action::Action;
-use crate::game::board::Owned;
-use crate::game::game_result::GameResult;
+use std::io::{self, stdin, stdout, Write};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::Duration;
+
+use crate::actor::{Actor, ActorError, Context, Decision, SearchReport};
+use crate::ai::mcts::MCTSActor;
 use crate::game::game_state::GameState;
+use crate::game::player::Player;
+use crate::game::notation::action_to_notation;
+use crate::game::record::GameRecord;
+
+use self::input::Input;
+use self::render::{render, RenderOptions};
+pub use crate::game::runner::{GameObserver, Match, Setup, Starter};
+
+pub mod input;
+pub mod render;
+#[cfg(feature = "tui")]
+pub mod tui;
+
+/// A player at the keyboard. Reads one move or command per line; see
+/// `input::HELP`.
+pub struct Human {
+    /// Suggests moves for `hint`.
+    hint: MCTSActor,
+}
 
-fn display(game_state: &mut GameState) -> String {
-    let current_sub_x = game_state.current_sub_x;
-    let current_sub_y = game_state.current_sub_y;
-
-    let mut string: String = String::new();
-
-    for sub_y in 0..3 {
-        string += "\n";
-
-        for y in 0..3 {
-            for sub_x in 0..3 {
-                string +=  if current_sub_y == Some(sub_y) &&
-                    current_sub_x == Some(sub_x) { "|" } else { " " };
-
-                for x in 0..3 {
-                    string += "|";
-
-                    let result = game_state
-                        .board
-                        .structure()
-                        .get(sub_x, sub_y)
-                        .structure()
-                        .get(x, y)
-                        .result();
-
-                    string += if result == Some(GameResult::Player1Wins) {
-                        "O"
-                    } else if result == Some(GameResult::Player2Wins) {
-                        "X"
-                    } else {
-                        " "
-                    }
-                }
-
-                string += "|";
-                string += if current_sub_y == Some(sub_y) &&
-                    current_sub_x == Some(sub_x) { "|" } else { " " }
-            }
+impl Human {
+    pub fn new() -> Human {
+        Human { hint: MCTSActor::new(1000) }
+    }
+
+    /// Uses this engine to answer `hint`.
+    pub fn with_hint(mut self, hint: MCTSActor) -> Human {
+        self.hint = hint;
+        self
+    }
 
-            string += "\n"
+    fn show_hint(&self, game_state: &GameState) {
+        for analysis in self.hint.analyse(game_state).iter().take(3) {
+            println!("{}  win {:.0}%  ({:.0}% of the search)",
+                     action_to_notation(&analysis.action), 100. * analysis.score, 100. * analysis.share);
         }
     }
+}
 
-    string
+impl Default for Human {
+    fn default() -> Human {
+        Human::new()
+    }
 }
 
-pub fn get_move(game_state: &mut GameState) -> Action {
-    let mut sub_x = String::new();
-    let mut sub_y = String::new();
-    let mut x = String::new();
-    let mut y = String::new();
-
-    println!("Player {}'s move!", game_state.current_player.num());
-
-    if game_state.current_sub_x.is_none() {
-        stdin().read_line(&mut sub_x).unwrap();
-        stdin().read_line(&mut sub_y).unwrap();
-    } else {
-        sub_x = game_state.current_sub_x.unwrap().to_string();
-        sub_y = game_state.current_sub_y.unwrap().to_string();
-    }
-    stdin().read_line(&mut x).unwrap();
-    stdin().read_line(&mut y).unwrap();
-
-    Action::new(
-        sub_x.replace("\n", "").parse::<usize>().unwrap(),
-        sub_y.replace("\n", "").parse::<usize>().unwrap(),
-        x.replace("\n", "").parse::<usize>().unwrap(),
-        y.replace("\n", "").parse::<usize>().unwrap(),
-        game_state.current_sub_x == None,
-    )
+/// How often a waiting player checks the clock and the cancellation token.
+const POLL: Duration = Duration::from_millis(50);
+
+/// The lines of standard input, read by a thread of their own so that waiting
+/// for them can be given up. `None` marks the end of the input.
+fn input_lines() -> &'static Mutex<Receiver<io::Result<Option<String>>>> {
+    static LINES: OnceLock<Mutex<Receiver<io::Result<Option<String>>>>> = OnceLock::new();
+
+    LINES.get_or_init(|| {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || loop {
+            let mut line = String::new();
+            let read = stdin().read_line(&mut line).map(|read| if read == 0 { None } else { Some(line) });
+            let more = matches!(read, Ok(Some(_)));
+            if sender.send(read).is_err() || !more {
+                return;
+            }
+        });
+        Mutex::new(receiver)
+    })
 }
 
-pub struct Human {}
+/// Prints a prompt and reads a line. The end of the input counts as the
+/// player leaving; the player also gives up when the clock runs out or the
+/// game is cancelled. A line typed after that is read at the next prompt.
+fn read_line(prompt: &str, context: &Context) -> Result<String, ActorError> {
+    print!("{}", prompt);
+    let _ = stdout().flush();
+
+    let lines = input_lines().lock().unwrap();
+    loop {
+        if context.cancel.is_cancelled() {
+            return Err(ActorError::Cancelled);
+        }
+        if context.clock.expired() {
+            return Err(ActorError::Timeout);
+        }
+
+        let wait = context.clock.remaining().map_or(POLL, |remaining| remaining.min(POLL));
+        match lines.recv_timeout(wait) {
+            Ok(Ok(Some(line))) => return Ok(line),
+            Ok(Ok(None)) | Err(RecvTimeoutError::Disconnected) => {
+                return Err(ActorError::Disconnected("end of input".to_string()))
+            },
+            Ok(Err(error)) => return Err(ActorError::Disconnected(error.to_string())),
+            Err(RecvTimeoutError::Timeout) => {},
+        }
+    }
+}
 
 impl Actor for Human {
-    fn get_action(&self, game_state: &mut GameState) -> Action {
-        get_move(game_state)
+    fn get_decision(&mut self, game_state: &GameState, context: &Context) -> Result<Decision, ActorError> {
+        loop {
+            let line = read_line("> ", context)?;
+
+            match input::parse(&line, game_state) {
+                Ok(Input::Move(action)) => return Ok(Decision::Move(action)),
+                Ok(Input::Undo) => return Ok(Decision::Undo),
+                Ok(Input::Resign) => return Ok(Decision::Resign),
+                Ok(Input::OfferDraw) => return Ok(Decision::OfferDraw),
+                Ok(Input::Quit) => return Ok(Decision::Quit),
+                Ok(Input::Hint) => self.show_hint(game_state),
+                Ok(Input::Save(path)) => match context.record.save(&path) {
+                    Ok(()) => println!("Saved to {}.", path),
+                    Err(error) => println!("Cannot write {}: {}", path, error),
+                },
+                Ok(Input::ShowMoves) => {
+                    let moves: Vec<String> = game_state.possible_actions().iter().map(action_to_notation).collect();
+                    println!("{}", moves.join(" "));
+                },
+                Ok(Input::Help) => println!("{}", input::HELP),
+                Err(message) => println!("{}", message),
+            }
+        }
+    }
+
+    fn accept_draw(&mut self, _game_state: &GameState, context: &Context) -> Result<bool, ActorError> {
+        loop {
+            match read_line("Your opponent offers a draw. Accept? (y/n) ", context)?.trim().to_lowercase().as_str() {
+                "y" | "yes" => return Ok(true),
+                "n" | "no" => return Ok(false),
+                _ => {},
+            }
+        }
     }
 }
 
-pub fn play(player1: &mut dyn Actor, player2: &mut dyn Actor) -> GameResult {
-    let mut game_state = GameState::new();
+/// The plain-text interface: prints the board and what happens in a game.
+pub struct Printer {
+    render_options: RenderOptions,
+}
 
-    loop {
-        println!("{}", display(&mut game_state));
+impl Printer {
+    pub fn new(render_options: RenderOptions) -> Printer {
+        Printer { render_options }
+    }
 
-        let action = player1.get_action(&mut game_state);
-        if let Some(result) = action.apply(&mut game_state) {
-            println!("{}", display(&mut game_state));
-            println!("Result: {}", result.to_string());
-            return result;
+    fn print_board(&self, game_state: &GameState, record: &GameRecord) {
+        let symbols = [record.players[0].symbol, record.players[1].symbol];
+        println!("{}", render(game_state, symbols, record.moves.last(), &self.render_options));
+    }
+}
+
+impl GameObserver for Printer {
+    fn turn_started(&mut self, player: Player, game_state: &GameState, record: &GameRecord) {
+        self.print_board(game_state, record);
+
+        let player = record.player(player);
+        println!("{}'s move ({})!", player.name, player.symbol);
+    }
+
+    fn search_reported(&mut self, _player: Player, report: &SearchReport) {
+        match report.proven {
+            Some(result) => println!("Proven result: {}", result),
+            None => {
+                println!("Number of simulations: {}", report.simulations);
+                println!("Expected result: {}", report.expected);
+            },
         }
+    }
 
-        println!("{}", display(&mut game_state));
+    fn nothing_to_undo(&mut self, _player: Player) {
+        println!("Nothing to undo.");
+    }
+
+    fn resigned(&mut self, player: Player, record: &GameRecord) {
+        println!("{} resigns.", record.player(player).name);
+    }
+
+    fn draw_offered(&mut self, player: Player, accepted: bool, record: &GameRecord) {
+        let answer = if accepted { "accepted" } else { "declined" };
+        println!("{} offers a draw, which is {}.", record.player(player).name, answer);
+    }
 
-        let action = player2.get_action(&mut game_state);
-        if let Some(result) = action.apply(&mut game_state) {
-            println!("{}", display(&mut game_state));
-            println!("Result: {}", result.to_string());
-            return result;
+    fn actor_failed(&mut self, player: Player, error: &ActorError, record: &GameRecord) {
+        println!("{} cannot go on: {}.", record.player(player).name, error);
+    }
+
+    fn game_ended(&mut self, game_state: &GameState, record: &GameRecord) {
+        if record.result.is_none() {
+            println!("Game abandoned.");
+            return;
         }
+
+        self.print_board(game_state, record);
+        println!("Result: {}", record.describe_result());
     }
 }
+
+/// Plays a game between two actors on the command line, printing it. See
+/// `Match::play`.
+pub fn play(setup: &Setup, first: &mut dyn Actor, second: &mut dyn Actor) -> GameRecord {
+    Match::new(setup.clone()).with_observer(Printer::new(RenderOptions::new())).play(first, second)
+}
@@ -0,0 +1,85 @@
+use crate::game::action::Action;
+use crate::game::game_state::GameState;
+
+pub const HELP: &str = "\
+Enter a move in one of these forms:
+  1102 or 1 1 0 2   sub-board x and y, then square x and y
+  02 or 0 2         square x and y, when the sub-board is forced
+  e6                column letter and row number on the full grid
+Commands: undo, hint, resign, draw, save <file>, show moves, quit, help";
+
+/// A line typed by a human player.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Input {
+    Move(Action),
+    Undo,
+    Hint,
+    Resign,
+    OfferDraw,
+    Save(String),
+    ShowMoves,
+    Quit,
+    Help,
+}
+
+/// Reads a move or command. Moves are checked against the legal moves of the
+/// position, and errors say what was wrong.
+pub fn parse(line: &str, game_state: &GameState) -> Result<Input, String> {
+    let line = line.trim();
+    let lower = line.to_lowercase();
+    let words: Vec<&str> = lower.split_whitespace().collect();
+
+    match words.as_slice() {
+        [] => Err("Enter a move, or 'help' for the accepted formats.".to_string()),
+        ["undo"] => Ok(Input::Undo),
+        ["hint"] => Ok(Input::Hint),
+        ["resign"] => Ok(Input::Resign),
+        ["draw"] => Ok(Input::OfferDraw),
+        ["show", "moves"] | ["moves"] => Ok(Input::ShowMoves),
+        ["quit"] | ["exit"] => Ok(Input::Quit),
+        ["help"] | ["?"] => Ok(Input::Help),
+        ["save"] => Err("Give a file to save to, as in 'save game.txt'.".to_string()),
+        ["save", ..] => Ok(Input::Save(line[4..].trim().to_string())),
+        _ => parse_move(&lower, game_state).map(Input::Move),
+    }
+}
+
+fn parse_move(line: &str, game_state: &GameState) -> Result<Action, String> {
+    let size = game_state.board().size();
+    let compact: String = line.chars().filter(|c| !c.is_whitespace() && *c != ',').collect();
+
+    let (sub_x, sub_y, x, y) = if compact.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        let column = compact.chars().next().unwrap() as usize - 'a' as usize;
+        let row = compact[1..].parse::<usize>()
+            .ok()
+            .filter(|&row| row >= 1)
+            .ok_or_else(|| format!("'{}' is not a square; rows are numbered from 1.", line))?
+            - 1;
+        (column / size, row / size, column % size, row % size)
+    } else {
+        let digits = compact.chars()
+            .map(|c| c.to_digit(10).map(|digit| digit as usize))
+            .collect::<Option<Vec<usize>>>()
+            .ok_or_else(|| format!("'{}' is neither a move nor a command; type 'help' for the formats.", line))?;
+
+        match (digits.as_slice(), game_state.current_sub_x, game_state.current_sub_y) {
+            (&[sub_x, sub_y, x, y], _, _) => (sub_x, sub_y, x, y),
+            (&[x, y], Some(sub_x), Some(sub_y)) => (sub_x, sub_y, x, y),
+            (&[_, _], _, _) => return Err("You may play in any sub-board, so give its coordinates as well.".to_string()),
+            _ => return Err(format!("'{}' has the wrong number of coordinates.", line)),
+        }
+    };
+
+    if [sub_x, sub_y, x, y].iter().any(|&coordinate| coordinate >= size) {
+        return Err(format!("Coordinates go from 0 to {}.", size - 1));
+    }
+
+    game_state.possible_actions()
+        .into_iter()
+        .find(|action| (action.sub_x, action.sub_y, action.x, action.y) == (sub_x, sub_y, x, y))
+        .ok_or_else(|| match (game_state.current_sub_x, game_state.current_sub_y) {
+            (Some(forced_x), Some(forced_y)) if (forced_x, forced_y) != (sub_x, sub_y) =>
+                format!("You have to play in sub-board {} {}.", forced_x, forced_y),
+            _ => "That square is not available.".to_string(),
+        })
+}
@@ -0,0 +1,169 @@
+use crate::game::action::Action;
+use crate::game::board::Owned;
+use crate::game::game_result::GameResult;
+use crate::game::game_state::GameState;
+
+/// What the plain-text board shows besides the squares.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct RenderOptions {
+    /// Column letters above and row numbers beside the board, as used for
+    /// moves given on the full grid.
+    pub coordinates: bool,
+    /// A small board beside the full one with the result of every sub-board
+    /// and a `*` where the player to move may play.
+    pub macro_summary: bool,
+    /// Covers won sub-boards with a large mark of the winner and drawn ones
+    /// with `=`.
+    pub overlays: bool,
+    /// Puts brackets around the last move.
+    pub last_move: bool,
+    /// Draws the grid with box-drawing characters instead of ASCII.
+    pub unicode: bool,
+}
+
+impl RenderOptions {
+    pub fn new() -> RenderOptions {
+        RenderOptions { coordinates: true, macro_summary: true, overlays: true, last_move: true, unicode: false }
+    }
+
+    /// Only the squares, as the board used to be printed.
+    pub fn plain() -> RenderOptions {
+        RenderOptions { coordinates: false, macro_summary: false, overlays: false, last_move: false, unicode: false }
+    }
+
+    pub fn with_coordinates(mut self, coordinates: bool) -> RenderOptions {
+        self.coordinates = coordinates;
+        self
+    }
+
+    pub fn with_macro_summary(mut self, macro_summary: bool) -> RenderOptions {
+        self.macro_summary = macro_summary;
+        self
+    }
+
+    pub fn with_overlays(mut self, overlays: bool) -> RenderOptions {
+        self.overlays = overlays;
+        self
+    }
+
+    pub fn with_last_move(mut self, last_move: bool) -> RenderOptions {
+        self.last_move = last_move;
+        self
+    }
+
+    pub fn with_unicode(mut self, unicode: bool) -> RenderOptions {
+        self.unicode = unicode;
+        self
+    }
+}
+
+impl Default for RenderOptions {
+    fn default() -> RenderOptions {
+        RenderOptions::new()
+    }
+}
+
+/// The squares of a won sub-board that show the winner's symbol: the
+/// diagonals for `X`, the border for anything else.
+pub(crate) fn in_overlay(symbol: char, x: usize, y: usize, size: usize) -> bool {
+    if symbol.eq_ignore_ascii_case(&'x') {
+        x == y || x + y == size - 1
+    } else {
+        x == 0 || y == 0 || x == size - 1 || y == size - 1
+    }
+}
+
+fn symbol(result: Option<GameResult>, symbols: [char; 2]) -> Option<char> {
+    match result {
+        Some(GameResult::Player1Wins) => Some(symbols[0]),
+        Some(GameResult::Player2Wins) => Some(symbols[1]),
+        _ => None,
+    }
+}
+
+/// Draws the board as text, with the symbols of player 1 and 2. Empty squares
+/// the player to move may play in are dotted; all others are blank.
+pub fn render(game_state: &GameState, symbols: [char; 2], last_move: Option<&Action>, options: &RenderOptions) -> String {
+    let size = game_state.board().size();
+    let squares = size * size;
+    let (vertical, horizontal, cross, empty) = if options.unicode { ('│', '─', '┼', '·') } else { ('|', '-', '+', '.') };
+    let margin = if options.coordinates { "   " } else { "" };
+
+    let mut playable = vec![false; squares];
+    if game_state.board().structure().full_result(game_state.rules()).is_none() {
+        for action in game_state.possible_actions() {
+            playable[size * action.sub_y + action.sub_x] = true;
+        }
+    }
+
+    let mut lines = Vec::new();
+    if options.coordinates {
+        let letters: Vec<String> = (0..size)
+            .map(|sub_x| (0..size).map(|x| format!(" {} ", (b'a' + (sub_x * size + x) as u8) as char)).collect())
+            .collect();
+        lines.push(format!("{}{}", margin, letters.join(" ")));
+    }
+
+    for row in 0..squares {
+        let mut line = if options.coordinates { format!("{:>2} ", row + 1) } else { String::new() };
+
+        for column in 0..squares {
+            let (sub_x, sub_y, x, y) = (column / size, row / size, column % size, row % size);
+            let sub_board = game_state.board().get(sub_x, sub_y);
+            if column > 0 && x == 0 {
+                line.push(vertical);
+            }
+
+            let square = symbol(sub_board.get(x, y).result(), symbols);
+            let open = playable[size * sub_y + sub_x];
+            let mark = match sub_board.result() {
+                Some(GameResult::Draw) if options.overlays && !open => '=',
+                Some(winner) if options.overlays && !open => {
+                    let winner = symbol(Some(winner), symbols).unwrap();
+                    if in_overlay(winner, x, y, size) { winner } else { ' ' }
+                },
+                _ => square.unwrap_or(if open { empty } else { ' ' }),
+            };
+
+            let last = options.last_move && last_move.is_some_and(|action| (action.sub_x, action.sub_y, action.x, action.y) == (sub_x, sub_y, x, y));
+            line += &if last { format!("[{}]", mark) } else { format!(" {} ", mark) };
+        }
+        lines.push(line);
+
+        if row % size == size - 1 && row < squares - 1 {
+            let segment: String = std::iter::repeat(horizontal).take(3 * size).collect();
+            lines.push(format!("{}{}", margin, vec![segment; size].join(&cross.to_string())));
+        }
+    }
+
+    if options.macro_summary {
+        let width = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+        let mut summary = vec!["Sub-boards".to_string()];
+        for sub_y in 0..size {
+            let marks: Vec<String> = (0..size).map(|sub_x| {
+                let result = game_state.board().get(sub_x, sub_y).result();
+                match (result, playable[size * sub_y + sub_x]) {
+                    (_, true) => '*',
+                    (Some(GameResult::Draw), _) => '=',
+                    (None, false) => empty,
+                    (result, _) => symbol(result, symbols).unwrap(),
+                }.to_string()
+            }).collect();
+            summary.push(marks.join(" "));
+        }
+
+        let first = if options.coordinates { 1 } else { 0 };
+        for (index, text) in summary.into_iter().enumerate() {
+            if first + index == lines.len() {
+                lines.push(String::new());
+            }
+            let line = &mut lines[first + index];
+            let padding = width - line.chars().count();
+            *line += &" ".repeat(padding + 4);
+            *line += &text;
+        }
+    }
+
+    let lines: Vec<&str> = lines.iter().map(|line| line.trim_end()).collect();
+    format!("\n{}\n", lines.join("\n"))
+}
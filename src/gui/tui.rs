@@ -0,0 +1,471 @@
+use std::cell::RefCell;
+use std::env;
+use std::io::{self, stdout, IsTerminal, Write};
+use std::time::Duration;
+
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Color, Print, ResetColor, SetAttribute, SetBackgroundColor, SetForegroundColor};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+
+use crate::actor::{Actor, ActorError, Context, Decision, SearchReport};
+use crate::ai::evaluator::{Evaluator, HeuristicEvaluator};
+use crate::ai::mcts::MCTSActor;
+use crate::game::board::Owned;
+use crate::game::game_result::GameResult;
+use crate::game::game_state::GameState;
+use crate::game::notation::action_to_notation;
+use crate::game::player::Player;
+use crate::game::record::GameRecord;
+use crate::gui::input::{self, Input};
+use crate::gui::render::in_overlay;
+use crate::game::runner::{GameObserver, Match};
+
+const KEYS: &str = "arrows: move  enter: play  ?: hint  u: undo  r: resign  d: offer draw  q: quit";
+const EVAL_WIDTH: usize = 20;
+
+/// Whether the terminal can show the full-screen interface. Dumb terminals and
+/// redirected output get the plain-text one.
+pub fn is_supported() -> bool {
+    stdout().is_terminal() && env::var("TERM").map_or(true, |term| term != "dumb")
+}
+
+fn colour(player: Player) -> Color {
+    match player {
+        Player::Player1 => Color::Cyan,
+        Player::Player2 => Color::Red,
+    }
+}
+
+fn dark_colour(player: Player) -> Color {
+    match player {
+        Player::Player1 => Color::DarkCyan,
+        Player::Player2 => Color::DarkRed,
+    }
+}
+
+fn owner(result: Option<GameResult>) -> Option<Player> {
+    match result {
+        Some(GameResult::Player1Wins) => Some(Player::Player1),
+        Some(GameResult::Player2Wins) => Some(Player::Player2),
+        _ => None,
+    }
+}
+
+/// The state of the full-screen interface: the cursor on the full grid,
+/// counted in squares from the top left, and the line shown below the board.
+pub struct Screen {
+    pub cursor: (usize, usize),
+    pub message: String,
+    hint: MCTSActor,
+    evaluator: HeuristicEvaluator,
+    /// Player 1's expected score from the last engine search, shown in the
+    /// evaluation bar until the position changes some other way.
+    reported: Option<f32>,
+}
+
+impl Screen {
+    pub fn new() -> Screen {
+        Screen {
+            cursor: (0, 0),
+            message: String::new(),
+            hint: MCTSActor::new(1000),
+            evaluator: HeuristicEvaluator::new(),
+            reported: None,
+        }
+    }
+
+    /// Uses this engine to answer `?`.
+    pub fn with_hint(mut self, hint: MCTSActor) -> Screen {
+        self.hint = hint;
+        self
+    }
+
+    /// Moves the cursor into the sub-board the player is sent to, unless it
+    /// is there already.
+    pub fn focus(&mut self, game_state: &GameState) {
+        let size = game_state.board().size();
+        let (column, row) = self.cursor;
+        self.cursor = (column.min(size * size - 1), row.min(size * size - 1));
+
+        if let (Some(sub_x), Some(sub_y)) = (game_state.current_sub_x, game_state.current_sub_y) {
+            if self.cursor.0 / size != sub_x || self.cursor.1 / size != sub_y {
+                self.cursor = (sub_x * size + size / 2, sub_y * size + size / 2);
+            }
+        }
+    }
+
+    /// Handles a key pressed by the player to move, returning their decision
+    /// once they make one.
+    pub fn handle_key(&mut self, key: KeyCode, game_state: &GameState) -> Option<Decision> {
+        let size = game_state.board().size();
+        let last = size * size - 1;
+        let (column, row) = self.cursor;
+        self.message.clear();
+
+        match key {
+            KeyCode::Left => self.cursor.0 = column.saturating_sub(1),
+            KeyCode::Right => self.cursor.0 = (column + 1).min(last),
+            KeyCode::Up => self.cursor.1 = row.saturating_sub(1),
+            KeyCode::Down => self.cursor.1 = (row + 1).min(last),
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                let notation = format!("{} {} {} {}", column / size, row / size, column % size, row % size);
+                match input::parse(&notation, game_state) {
+                    Ok(Input::Move(action)) => return Some(Decision::Move(action)),
+                    Ok(_) => {},
+                    Err(message) => self.message = message,
+                }
+            },
+            KeyCode::Char('?') => self.show_hint(game_state),
+            KeyCode::Char('u') => return Some(Decision::Undo),
+            KeyCode::Char('r') => return Some(Decision::Resign),
+            KeyCode::Char('d') => return Some(Decision::OfferDraw),
+            KeyCode::Char('q') | KeyCode::Esc => return Some(Decision::Quit),
+            _ => {},
+        }
+
+        None
+    }
+
+    fn show_hint(&mut self, game_state: &GameState) {
+        let size = game_state.board().size();
+
+        if let Some(best) = self.hint.analyse(game_state).first() {
+            let action = &best.action;
+            self.cursor = (action.sub_x * size + action.x, action.sub_y * size + action.y);
+            self.message = format!("Hint: {} (win {:.0}%)", action_to_notation(action), 100. * best.score);
+        }
+    }
+
+    /// Shows the expected score of `player`'s last search in the evaluation
+    /// bar.
+    pub fn show_report(&mut self, player: Player, report: &SearchReport) {
+        self.reported = Some(if player == Player::Player1 { report.expected } else { 1. - report.expected });
+    }
+
+    /// Goes back to the heuristic evaluation, as the position no longer follows
+    /// from the last search.
+    pub fn forget_report(&mut self) {
+        self.reported = None;
+    }
+
+    /// Player 1's expected score: the result once there is one, otherwise the
+    /// last engine search's, or the heuristic's without one.
+    fn evaluation(&self, game_state: &GameState, record: &GameRecord) -> f32 {
+        match (record.result, self.reported) {
+            (Some(result), _) => result.score(Player::Player1),
+            (None, Some(reported)) => reported,
+            (None, None) => {
+                let score = self.evaluator.evaluate(game_state);
+                if game_state.current_player() == Player::Player1 { score } else { 1. - score }
+            },
+        }
+    }
+
+    /// Draws the whole screen: the board with the move list and evaluation
+    /// beside it, and `status`, the message and the keys below it. The cursor
+    /// is only shown when `cursor` is set.
+    pub fn draw<W: Write>(&self, out: &mut W, game_state: &GameState, record: &GameRecord, status: &str, cursor: bool) -> io::Result<()> {
+        let size = game_state.board().size();
+        let squares = size * size;
+        let height = squares + size - 1;
+        let panel = (3 * squares + size + 2) as u16;
+
+        queue!(out, ResetColor, Clear(ClearType::All))?;
+
+        let mut playable = vec![false; squares];
+        if record.result.is_none() {
+            for action in game_state.possible_actions() {
+                playable[size * action.sub_y + action.sub_x] = true;
+            }
+        }
+
+        let mut line = 0;
+        for row in 0..squares {
+            queue!(out, MoveTo(0, line))?;
+            for column in 0..squares {
+                if column > 0 && column % size == 0 {
+                    queue!(out, Print('│'))?;
+                }
+                self.draw_square(out, game_state, record, (column, row), playable[size * (row / size) + column / size], cursor)?;
+            }
+            line += 1;
+
+            if row % size == size - 1 && row < squares - 1 {
+                let segment = "─".repeat(3 * size);
+                queue!(out, MoveTo(0, line), Print(vec![segment; size].join("┼")))?;
+                line += 1;
+            }
+        }
+
+        for (index, player) in [Player::Player1, Player::Player2].iter().enumerate() {
+            let config = record.player(*player);
+            queue!(out, MoveTo(panel, index as u16),
+                   SetForegroundColor(colour(*player)), Print(config.symbol), ResetColor,
+                   Print(format!(" {}", config.name)))?;
+        }
+
+        let evaluation = self.evaluation(game_state, record);
+        let filled = (evaluation * EVAL_WIDTH as f32).round() as usize;
+        queue!(out, MoveTo(panel, 3),
+               SetForegroundColor(colour(Player::Player1)), Print("█".repeat(filled)),
+               SetForegroundColor(colour(Player::Player2)), Print("█".repeat(EVAL_WIDTH - filled)),
+               ResetColor, Print(format!(" {:.0}%", 100. * evaluation)))?;
+
+        let moves: Vec<String> = record.moves.iter().map(action_to_notation).collect();
+        let lines: Vec<String> = moves.chunks(2)
+            .enumerate()
+            .map(|(index, pair)| format!("{:>3}. {}", index + 1, pair.join(" ")))
+            .collect();
+        let shown = (height.max(10) - 5).min(lines.len());
+        queue!(out, MoveTo(panel, 5), Print("Moves"))?;
+        for (index, text) in lines[lines.len() - shown..].iter().enumerate() {
+            queue!(out, MoveTo(panel, 6 + index as u16), Print(text))?;
+        }
+
+        let below = height.max(10) as u16 + 1;
+        queue!(out, MoveTo(0, below), Print(status),
+               MoveTo(0, below + 1), Print(&self.message),
+               MoveTo(0, below + 3), SetForegroundColor(Color::DarkGrey), Print(KEYS), ResetColor)?;
+
+        out.flush()
+    }
+
+    fn draw_square<W: Write>(&self, out: &mut W, game_state: &GameState, record: &GameRecord, (column, row): (usize, usize), playable: bool, cursor: bool) -> io::Result<()> {
+        let size = game_state.board().size();
+        let (sub_x, sub_y, x, y) = (column / size, row / size, column % size, row % size);
+        let sub_board = game_state.board().get(sub_x, sub_y);
+        let symbol = |player: Player| record.player(player).symbol;
+
+        let (mut text, mut foreground, mut background) = match owner(sub_board.get(x, y).result()) {
+            Some(player) => (symbol(player), colour(player), Color::Reset),
+            None => ('·', if playable { Color::Grey } else { Color::DarkGrey }, Color::Reset),
+        };
+        if playable {
+            background = Color::DarkGrey;
+        }
+
+        match sub_board.result() {
+            Some(GameResult::Draw) => foreground = Color::DarkGrey,
+            result => if let Some(winner) = owner(result) {
+                let overlay = in_overlay(symbol(winner), x, y, size);
+                text = if overlay { symbol(winner) } else { ' ' };
+                foreground = Color::White;
+                background = dark_colour(winner);
+            },
+        }
+
+        let last_move = record.moves.last().is_some_and(|action| (action.sub_x, action.sub_y, action.x, action.y) == (sub_x, sub_y, x, y));
+        if last_move {
+            queue!(out, SetAttribute(Attribute::Bold), SetAttribute(Attribute::Underlined))?;
+        }
+        if cursor && self.cursor == (column, row) {
+            queue!(out, SetAttribute(Attribute::Reverse))?;
+        }
+
+        queue!(out, SetForegroundColor(foreground), SetBackgroundColor(background),
+               Print(format!(" {} ", text)), SetAttribute(Attribute::Reset), ResetColor)
+    }
+}
+
+impl Default for Screen {
+    fn default() -> Screen {
+        Screen::new()
+    }
+}
+
+/// Raw mode on the alternate screen, restored when dropped.
+struct Terminal;
+
+impl Terminal {
+    fn enter() -> io::Result<Terminal> {
+        terminal::enable_raw_mode()?;
+        execute!(stdout(), EnterAlternateScreen, Hide)?;
+        Ok(Terminal)
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        let _ = execute!(stdout(), Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+fn read_key() -> io::Result<KeyCode> {
+    loop {
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Release {
+                continue;
+            }
+            if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+                return Ok(KeyCode::Esc);
+            }
+            return Ok(key.code);
+        }
+    }
+}
+
+/// Waits for a key while the player may still decide: until the game is
+/// cancelled or their clock runs out.
+fn read_key_in(context: &Context) -> Result<KeyCode, ActorError> {
+    let disconnected = |error: io::Error| ActorError::Disconnected(error.to_string());
+
+    loop {
+        if context.cancel.is_cancelled() {
+            return Err(ActorError::Cancelled);
+        }
+        if context.clock.expired() {
+            return Err(ActorError::Timeout);
+        }
+        if event::poll(Duration::from_millis(100)).map_err(disconnected)? {
+            return read_key().map_err(disconnected);
+        }
+    }
+}
+
+/// A player at the keyboard of the full-screen interface. Terminal errors
+/// are kept for `play` to return, and end the game.
+struct Keyboard<'a, 's> {
+    screen: &'a RefCell<&'s mut Screen>,
+    error: &'a RefCell<Option<io::Error>>,
+}
+
+impl Keyboard<'_, '_> {
+    fn keep<T>(&self, result: io::Result<T>) -> Result<T, ActorError> {
+        result.map_err(|error| {
+            let failure = ActorError::Disconnected(error.to_string());
+            self.error.borrow_mut().get_or_insert(error);
+            failure
+        })
+    }
+}
+
+impl Actor for Keyboard<'_, '_> {
+    fn get_decision(&mut self, game_state: &GameState, context: &Context) -> Result<Decision, ActorError> {
+        let mut screen = self.screen.borrow_mut();
+        let player = context.record.player(game_state.current_player());
+        let status = format!("{}'s move ({})", player.name, player.symbol);
+
+        loop {
+            self.keep(screen.draw(&mut stdout(), game_state, context.record, &status, true))?;
+            if let Some(decision) = screen.handle_key(read_key_in(context)?, game_state) {
+                return Ok(decision);
+            }
+        }
+    }
+
+    fn accept_draw(&mut self, game_state: &GameState, context: &Context) -> Result<bool, ActorError> {
+        let screen = self.screen.borrow();
+        let player = context.record.player(game_state.current_player());
+        let status = format!("{} offers a draw. Accept? (y/n)", player.name);
+
+        loop {
+            self.keep(screen.draw(&mut stdout(), game_state, context.record, &status, false))?;
+            match read_key_in(context)? {
+                KeyCode::Char('y') => return Ok(true),
+                KeyCode::Char('n') | KeyCode::Esc => return Ok(false),
+                _ => {},
+            }
+        }
+    }
+}
+
+/// Draws the screen as the game goes on. Errors are kept for `play` to
+/// return.
+struct Display<'a, 's> {
+    screen: &'a RefCell<&'s mut Screen>,
+    error: &'a RefCell<Option<io::Error>>,
+    /// Whether an actor failed, which is shown before leaving.
+    failed: bool,
+}
+
+impl Display<'_, '_> {
+    fn keep(&self, result: io::Result<()>) {
+        if let Err(error) = result {
+            self.error.borrow_mut().get_or_insert(error);
+        }
+    }
+}
+
+impl GameObserver for Display<'_, '_> {
+    fn turn_started(&mut self, player: Player, game_state: &GameState, record: &GameRecord) {
+        let mut screen = self.screen.borrow_mut();
+        screen.focus(game_state);
+
+        let status = format!("{} is thinking...", record.player(player).name);
+        self.keep(screen.draw(&mut stdout(), game_state, record, &status, false));
+    }
+
+    fn search_reported(&mut self, player: Player, report: &SearchReport) {
+        let mut screen = self.screen.borrow_mut();
+        screen.show_report(player, report);
+        screen.message = match report.proven {
+            Some(result) => format!("Proven result: {}", result),
+            None => format!("{} simulations, expecting {:.0}%", report.simulations, 100. * report.expected),
+        };
+    }
+
+    fn move_undone(&mut self, _game_state: &GameState, _record: &GameRecord) {
+        self.screen.borrow_mut().forget_report();
+    }
+
+    fn nothing_to_undo(&mut self, _player: Player) {
+        self.screen.borrow_mut().message = "Nothing to undo.".to_string();
+    }
+
+    fn resigned(&mut self, player: Player, record: &GameRecord) {
+        self.screen.borrow_mut().message = format!("{} resigns.", record.player(player).name);
+    }
+
+    fn draw_offered(&mut self, player: Player, accepted: bool, record: &GameRecord) {
+        let answer = if accepted { "accepted" } else { "declined" };
+        self.screen.borrow_mut().message = format!("{} offers a draw, which is {}.", record.player(player).name, answer);
+    }
+
+    fn actor_failed(&mut self, player: Player, error: &ActorError, record: &GameRecord) {
+        self.failed = self.error.borrow().is_none();
+        self.screen.borrow_mut().message = format!("{} cannot go on: {}.", record.player(player).name, error);
+    }
+
+    fn game_ended(&mut self, game_state: &GameState, record: &GameRecord) {
+        if record.result.is_none() && !self.failed {
+            return;
+        }
+
+        let mut screen = self.screen.borrow_mut();
+        screen.message = format!("{} Press any key to leave.", screen.message).trim_start().to_string();
+        let status = format!("Result: {}", record.describe_result());
+        self.keep(screen.draw(&mut stdout(), game_state, record, &status, false).and_then(|_| read_key().map(|_| ())));
+    }
+}
+
+/// Plays a game like `gui::play` on the full screen. Players without an actor
+/// are at the keyboard.
+pub fn play(game: Match, screen: &mut Screen, first: Option<&mut dyn Actor>, second: Option<&mut dyn Actor>) -> io::Result<GameRecord> {
+    let screen = RefCell::new(screen);
+    let error = RefCell::new(None);
+    let _terminal = Terminal::enter()?;
+
+    let mut game = game.with_observer(Display { screen: &screen, error: &error, failed: false });
+
+    let mut keyboards = [Keyboard { screen: &screen, error: &error }, Keyboard { screen: &screen, error: &error }];
+    let [first_keyboard, second_keyboard] = &mut keyboards;
+    let first: &mut dyn Actor = match first {
+        Some(actor) => actor,
+        None => first_keyboard,
+    };
+    let second: &mut dyn Actor = match second {
+        Some(actor) => actor,
+        None => second_keyboard,
+    };
+
+    let record = game.play(first, second);
+    drop(game);
+
+    match error.into_inner() {
+        Some(error) => Err(error),
+        None => Ok(record),
+    }
+}
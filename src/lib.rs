@@ -1,7 +1,8 @@
 extern crate rand;
 
-mod actor;
+pub mod actor;
 pub mod ai;
-mod game;
+pub mod cli;
+pub mod game;
 pub mod gui;
 mod util;
@@ -1,6 +1,14 @@
-use ultimate_ttt::ai::mcts::MCTSActor;
-use ultimate_ttt::gui;
+use std::env;
+use std::process;
+
+use ultimate_ttt::cli;
 
 fn main() {
-    gui::play(&mut MCTSActor::new(1000), &mut gui::Human{});
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let result = cli::parse(&args).and_then(|options| cli::run(&options));
+    if let Err(message) = result {
+        eprintln!("{}\n\n{}", message, cli::USAGE);
+        process::exit(1);
+    }
 }
@@ -0,0 +1,60 @@
+use ultimate_ttt::ai::analysis::{review, Judgement, BLUNDER, MISTAKE};
+use ultimate_ttt::ai::mcts::MCTSActor;
+use ultimate_ttt::game::action::Action;
+use ultimate_ttt::game::game_result::GameResult;
+use ultimate_ttt::game::notation::from_notation;
+use ultimate_ttt::game::player::Player;
+use ultimate_ttt::game::record::GameRecord;
+
+/// O wins by playing 2020.
+const LINE_IN_ONE: &str = "OOOOOOOO./........./........./XXX....../X......../X.X....../........./........./......... 1 2";
+
+fn engine() -> MCTSActor {
+    MCTSActor::new(0).with_iterations(2000).with_endgame_threshold(0)
+}
+
+#[test]
+fn analysis_ranks_moves_with_their_scores() {
+    let game_state = from_notation(LINE_IN_ONE).unwrap();
+    let analysis = engine().analyse(&game_state);
+
+    assert_eq!(analysis.len(), game_state.possible_actions().len());
+    assert_eq!(analysis[0].action, Action::new(2, 0, 2, 0, false));
+    assert_eq!(analysis[0].score, 1.);
+
+    let total: f32 = analysis.iter().map(|analysis| analysis.share).sum();
+    assert!((total - 1.).abs() < 1e-3);
+    for pair in analysis.windows(2) {
+        assert!(pair[0].share >= pair[1].share);
+    }
+    assert!(analysis.iter().all(|analysis| (0. ..=1.).contains(&analysis.score)));
+}
+
+#[test]
+fn losses_are_judged_by_size() {
+    assert_eq!(Judgement::from_loss(0.), Judgement::Good);
+    assert_eq!(Judgement::from_loss(MISTAKE - 0.01), Judgement::Good);
+    assert_eq!(Judgement::from_loss(MISTAKE), Judgement::Mistake);
+    assert_eq!(Judgement::from_loss(BLUNDER), Judgement::Blunder);
+    assert_eq!(Judgement::from_loss(1.), Judgement::Blunder);
+}
+
+#[test]
+fn review_marks_a_missed_win() {
+    let start = from_notation(LINE_IN_ONE).unwrap();
+
+    let mut missed = GameRecord::new(start.clone());
+    missed.push(Action::new(2, 0, 0, 1, false), None);
+    let reviews = review(&missed, &engine());
+    assert_eq!(reviews.len(), 1);
+    assert_eq!(reviews[0].player, Player::Player1);
+    assert_eq!(reviews[0].best.action, Action::new(2, 0, 2, 0, false));
+    assert!(reviews[0].loss() >= MISTAKE);
+    assert_ne!(reviews[0].judgement, Judgement::Good);
+
+    let mut won = GameRecord::new(start);
+    won.push(Action::new(2, 0, 2, 0, false), Some(GameResult::Player1Wins));
+    let reviews = review(&won, &engine());
+    assert_eq!(reviews[0].score, 1.);
+    assert_eq!(reviews[0].judgement, Judgement::Good);
+}
@@ -0,0 +1,106 @@
+use ultimate_ttt::actor::{Actor, ActorError, Context, Decision};
+use ultimate_ttt::ai::baseline::{GreedyActor, RandomActor, ScriptedActor};
+use ultimate_ttt::game::game_result::GameResult;
+use ultimate_ttt::game::game_state::GameState;
+use ultimate_ttt::game::notation::{action_from_notation, from_notation};
+use ultimate_ttt::game::player::Player;
+use ultimate_ttt::game::record::GameRecord;
+use ultimate_ttt::gui::{Match, Setup};
+
+/// Player 1 to move in sub-board 0 0, with two in a row there.
+const SUB_BOARD_IN_ONE: &str = "OO......./........./........./OOO....../...XXX.../........./X......../........./........X 1 00";
+
+fn setup() -> Setup {
+    Setup::new()
+}
+
+#[test]
+fn seeded_random_games_repeat() {
+    let play = || Match::new(setup()).play(&mut RandomActor::new().with_seed(1), &mut RandomActor::new().with_seed(2));
+
+    let first = play();
+    assert!(first.result.is_some());
+    assert_eq!(first.moves, play().moves);
+}
+
+#[test]
+fn greedy_wins_a_sub_board_when_it_can() {
+    let game_state = from_notation(SUB_BOARD_IN_ONE).unwrap();
+    let record = GameRecord::new(game_state.clone());
+
+    for seed in 0..5 {
+        let decision = GreedyActor::new().with_seed(seed).get_decision(&game_state, &Context::new(&record));
+        assert_eq!(decision, Ok(Decision::Move(action_from_notation("0020", &game_state).unwrap())));
+    }
+}
+
+#[test]
+fn greedy_beats_random() {
+    let mut greedy = GreedyActor::new().with_seed(7);
+    let mut random = RandomActor::new().with_seed(7);
+
+    let mut wins = 0;
+    for _ in 0..10 {
+        let record = Match::new(setup()).play(&mut greedy, &mut random);
+        if record.result == Some(GameResult::Player1Wins) {
+            wins += 1;
+        }
+    }
+
+    assert!(wins >= 7, "greedy won only {} of 10", wins);
+}
+
+#[test]
+fn scripted_actors_replay_a_record() {
+    let original = Match::new(setup()).play(&mut RandomActor::new().with_seed(3), &mut GreedyActor::new().with_seed(3));
+
+    let replay = Match::new(setup()).play(&mut ScriptedActor::new(original.clone()), &mut ScriptedActor::new(original.clone()));
+
+    assert_eq!(replay.moves, original.moves);
+    assert_eq!(replay.result, original.result);
+}
+
+#[test]
+fn scripted_actors_resign_and_agree_to_draws_as_recorded() {
+    let mut resigned = GameRecord::new(GameState::new());
+    let opening = action_from_notation("1111", &GameState::new()).unwrap();
+    resigned.push(opening.clone(), None);
+    resigned.resign(Player::Player2);
+
+    let replay = Match::new(setup()).play(&mut ScriptedActor::new(resigned.clone()), &mut ScriptedActor::new(resigned));
+    assert_eq!(replay.moves, vec![opening.clone()]);
+    assert_eq!(replay.result, Some(GameResult::Player1Wins));
+
+    let mut drawn = GameRecord::new(GameState::new());
+    drawn.push(opening, None);
+    drawn.agree_draw();
+
+    let replay = Match::new(setup()).play(&mut ScriptedActor::new(drawn.clone()), &mut ScriptedActor::new(drawn));
+    assert_eq!(replay.result, Some(GameResult::Draw));
+}
+
+#[test]
+fn scripted_draw_offers_are_made_once() {
+    let mut drawn = GameRecord::new(GameState::new());
+    drawn.agree_draw();
+
+    let replay = Match::new(setup()).play(&mut ScriptedActor::new(drawn), &mut RandomActor::new().with_seed(1));
+    assert_eq!(replay.result, None);
+    assert!(replay.moves.is_empty());
+}
+
+#[test]
+fn scripted_actors_fail_off_the_script() {
+    let mut record = GameRecord::new(GameState::new());
+    record.push(action_from_notation("1111", &GameState::new()).unwrap(), None);
+    let mut scripted = ScriptedActor::new(record.clone());
+
+    let mut game_state = GameState::new();
+    action_from_notation("0000", &game_state).unwrap().apply(&mut game_state);
+    let context = Context::new(&record);
+    assert!(matches!(scripted.get_decision(&game_state, &context), Err(ActorError::Failed(_))));
+
+    let after = record.final_state();
+    assert!(matches!(scripted.get_decision(&after, &context), Err(ActorError::Failed(_))));
+    assert_eq!(scripted.get_decision(&GameState::new(), &context), Ok(Decision::Move(record.moves[0].clone())));
+}
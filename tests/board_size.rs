@@ -0,0 +1,159 @@
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+use ultimate_ttt::actor::{Actor, Context, Decision};
+use ultimate_ttt::ai::mcts::MCTSActor;
+use ultimate_ttt::game::action::Action;
+use ultimate_ttt::game::board::{lines, BoardStructure, Owned, Square, LINES};
+use ultimate_ttt::game::game_result::GameResult;
+use ultimate_ttt::game::game_state::GameState;
+use ultimate_ttt::game::notation::{from_notation, to_notation};
+use ultimate_ttt::game::record::GameRecord;
+use ultimate_ttt::game::rules::Rules;
+use ultimate_ttt::game::symmetry::SYMMETRIES;
+
+fn rules(size: usize, win_length: usize) -> Rules {
+    Rules::new().with_size(size, win_length)
+}
+
+/// The move the actor decides on, asked outside any game.
+fn decide(actor: &mut dyn Actor, game_state: &GameState) -> Action {
+    let record = GameRecord::new(game_state.clone());
+    match actor.get_decision(game_state, &Context::new(&record)) {
+        Ok(Decision::Move(action)) => action,
+        other => panic!("expected a move, got {:?}", other),
+    }
+}
+
+#[test]
+fn lines_cover_every_stretch() {
+    assert_eq!(lines(3, 3).len(), LINES.len());
+    for line in LINES.iter() {
+        assert!(lines(3, 3).contains(&line.to_vec()), "line {:?}", line);
+    }
+
+    assert_eq!(lines(4, 4).len(), 10);
+    assert_eq!(lines(4, 3).len(), 24);
+    assert_eq!(lines(4, 2).len(), 42);
+}
+
+#[test]
+fn check_result_needs_win_length_in_a_row() {
+    for &(win_length, expected) in [(3, Some(GameResult::Player1Wins)), (4, None)].iter() {
+        for line in lines(4, 3) {
+            let mut structure = <BoardStructure<Square>>::with_size(4);
+            for &(x, y) in line.iter() {
+                structure.set_result_at(x, y, Some(GameResult::Player1Wins));
+            }
+
+            for &(x, y) in line.iter() {
+                assert_eq!(structure.check_result(x, y, &rules(4, win_length)), expected, "line {:?}", line);
+            }
+            assert_eq!(structure.full_result(&rules(4, win_length)), expected, "line {:?}", line);
+        }
+    }
+
+    for line in lines(4, 4) {
+        let mut structure = <BoardStructure<Square>>::with_size(4);
+        for &(x, y) in line.iter() {
+            structure.set_result_at(x, y, Some(GameResult::Player2Wins));
+        }
+
+        let (x, y) = line[3];
+        assert_eq!(structure.check_result(x, y, &rules(4, 4)), Some(GameResult::Player2Wins), "line {:?}", line);
+    }
+}
+
+#[test]
+fn perft_on_larger_boards() {
+    let game_state = GameState::with_rules(rules(4, 3));
+    assert_eq!(game_state.possible_actions().len(), 256);
+    // Every move sends the opponent to an empty sub-board, except for the 16
+    // moves that send them back to the sub-board just played in.
+    assert_eq!(game_state.perft(2), 240 * 16 + 16 * 15);
+
+    assert_eq!(GameState::with_rules(rules(4, 4)).perft(2), 240 * 16 + 16 * 15);
+    assert_eq!(GameState::with_rules(rules(2, 2)).perft(2), 12 * 4 + 4 * 3);
+}
+
+#[test]
+fn notation_round_trips_on_larger_boards() {
+    let mut rng = StdRng::seed_from_u64(37);
+
+    for &(size, win_length) in [(2, 2), (3, 3), (3, 2), (4, 3), (4, 4)].iter() {
+        let start = GameState::with_rules(rules(size, win_length));
+        assert_eq!(from_notation(&to_notation(&start)).unwrap().rules(), start.rules());
+
+        for _ in 0..20 {
+            let (end, _) = start.play_out(&mut rng);
+            let parsed = from_notation(&to_notation(&end)).unwrap();
+
+            assert_eq!(to_notation(&parsed), to_notation(&end));
+            assert_eq!(parsed.rules(), end.rules());
+        }
+    }
+
+    assert!(from_notation("..../..../..../.... 1 -").unwrap().rules() == &rules(2, 2));
+    assert!(from_notation("................/................/................/................/................/................/................/................/................/................/................/................/................/................/................/................ 1 - 3").is_ok());
+    assert!(from_notation("..../..../..../.... 1 - 3").is_err());
+    assert!(from_notation("..../..../..../.... 1 4").is_err());
+}
+
+#[test]
+fn random_games_on_larger_boards_are_consistent() {
+    let mut rng = StdRng::seed_from_u64(4);
+
+    for &(size, win_length) in [(4, 3), (4, 4)].iter() {
+        let rules = rules(size, win_length);
+
+        for _ in 0..100 {
+            let (end, result) = GameState::with_rules(rules).play_out(&mut rng);
+
+            for sub_board in end.board().structure().items() {
+                assert_eq!(sub_board.result(), sub_board.structure().full_result(&rules), "{}", to_notation(&end));
+            }
+            assert_eq!(end.board().structure().full_result(&rules), Some(result), "{}", to_notation(&end));
+        }
+    }
+}
+
+#[test]
+fn symmetries_preserve_moves_on_larger_boards() {
+    let mut rng = StdRng::seed_from_u64(16);
+    let start = GameState::with_rules(rules(4, 3));
+
+    for _ in 0..20 {
+        let mut game_state = start.clone();
+        for _ in 0..rng.gen_range(0, 40) {
+            let action = game_state.possible_actions().choose(&mut rng).unwrap().clone();
+            if action.apply(&mut game_state).is_some() {
+                break;
+            }
+        }
+
+        for &symmetry in SYMMETRIES.iter() {
+            let transformed = symmetry.transform_state(&game_state);
+            let mut expected: Vec<String> = game_state.possible_actions().iter()
+                .map(|action| format!("{:?}", symmetry.transform_action(action, 4)))
+                .collect();
+            let mut actual: Vec<String> = transformed.possible_actions().iter().map(|action| format!("{:?}", action)).collect();
+            expected.sort();
+            actual.sort();
+
+            assert_eq!(actual, expected);
+        }
+    }
+}
+
+#[test]
+fn mcts_plays_legal_moves_on_larger_boards() {
+    let mut actor = MCTSActor::new(50);
+    let mut game_state = GameState::with_rules(rules(4, 3));
+
+    for _ in 0..2 {
+        let action = decide(&mut actor, &game_state);
+        assert!(game_state.possible_actions().contains(&action));
+        action.apply(&mut game_state);
+    }
+}
@@ -0,0 +1,152 @@
+use ultimate_ttt::actor::{Actor, ActorError, Context, Decision, SearchReport};
+use ultimate_ttt::ai::book::{BookActor, BookBuilder, OpeningBook};
+use ultimate_ttt::game::action::Action;
+use ultimate_ttt::game::game_state::GameState;
+use ultimate_ttt::game::record::GameRecord;
+use ultimate_ttt::game::rules::{Rules, WinCondition};
+use ultimate_ttt::game::symmetry::SYMMETRIES;
+
+fn after(actions: &[Action]) -> GameState {
+    let mut game_state = GameState::new();
+    for action in actions {
+        action.apply(&mut game_state);
+    }
+
+    game_state
+}
+
+/// A move's square, which can be compared.
+type Square = (usize, usize, usize, usize);
+
+fn square(action: &Action) -> Square {
+    (action.sub_x, action.sub_y, action.x, action.y)
+}
+
+fn squares(moves: Option<Vec<(Action, u32)>>) -> Option<Vec<(Square, u32)>> {
+    moves.map(|moves| moves.iter().map(|(action, weight)| (square(action), *weight)).collect())
+}
+
+fn book() -> OpeningBook {
+    let mut book = OpeningBook::new();
+    book.insert(&GameState::new(), vec![(Action::new(1, 1, 1, 1, true), 10), (Action::new(0, 0, 0, 0, true), 3)]);
+    book.insert(&after(&[Action::new(0, 0, 2, 1, true)]), vec![(Action::new(2, 1, 0, 0, false), 7)]);
+    book
+}
+
+#[test]
+fn books_survive_a_round_trip() {
+    let book = book();
+    let mut bytes = Vec::new();
+    book.write(&mut bytes).unwrap();
+
+    let read = OpeningBook::read(&mut bytes.as_slice()).unwrap();
+    assert_eq!(read.len(), 2);
+    for game_state in [GameState::new(), after(&[Action::new(0, 0, 2, 1, true)])].iter() {
+        assert_eq!(squares(read.lookup(game_state)), squares(book.lookup(game_state)));
+    }
+
+    let mut again = Vec::new();
+    read.write(&mut again).unwrap();
+    assert_eq!(again, bytes);
+}
+
+#[test]
+fn other_files_are_not_books() {
+    assert!(OpeningBook::read(&mut &b"UTNN\x01\0\0\0\0\0\0\0"[..]).is_err());
+    assert!(OpeningBook::read(&mut &b"UTBK\x01\0\0\0\x01\0\0\0"[..]).is_err());
+}
+
+#[test]
+fn books_with_illegal_moves_are_rejected() {
+    // Player 1 has taken the top-left square and player 2 is to move anywhere.
+    let entry = |cell: u8| {
+        let mut bytes = b"UTBK\x01\0\0\0\x01\0\0\0".to_vec();
+        bytes.push(1);
+        bytes.extend_from_slice(&[0; 20]);
+        bytes.extend_from_slice(&[(2 << 4) | 9, 1, cell, 1, 0]);
+        bytes
+    };
+
+    assert!(OpeningBook::read(&mut entry(1).as_slice()).is_ok());
+    for &cell in [0, 81, 255].iter() {
+        let error = OpeningBook::read(&mut entry(cell).as_slice()).err().unwrap();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData, "cell {}", cell);
+    }
+}
+
+#[test]
+fn books_only_cover_the_standard_rules() {
+    let misere = GameState::with_rules(Rules::new().with_win_condition(WinCondition::Misere));
+    let mut book = book();
+    assert!(book.lookup(&misere).is_none());
+
+    book.insert(&misere, vec![(Action::new(0, 0, 0, 0, true), 1)]);
+    assert_eq!(book.len(), 2);
+    assert!(book.lookup(&GameState::new()).is_some());
+}
+
+#[test]
+fn lookups_find_every_symmetric_image() {
+    let book = book();
+    let game_state = after(&[Action::new(0, 0, 2, 1, true)]);
+    let reply = Action::new(2, 1, 0, 0, false);
+
+    for &symmetry in SYMMETRIES.iter() {
+        let image = symmetry.transform_state(&game_state);
+        let moves = book.lookup(&image).unwrap_or_else(|| panic!("no entry for {:?}", symmetry));
+        let expected = symmetry.transform_action(&reply, 3);
+
+        assert_eq!(squares(Some(moves.clone())), squares(Some(vec![(expected, 7)])), "{:?}", symmetry);
+        assert!(image.possible_actions().iter().any(|action| square(action) == square(&moves[0].0)));
+    }
+
+    let elsewhere = after(&[Action::new(1, 1, 0, 0, true)]);
+    assert!(book.lookup(&elsewhere).is_none());
+}
+
+#[test]
+fn building_reports_its_progress() {
+    let mut progress = Vec::new();
+    let book = BookBuilder::new(2, 20).with_width(1).build(None, &mut |positions, ply| progress.push((positions, ply)));
+
+    assert_eq!(book.len(), 2);
+    assert_eq!(progress, vec![(1, 0), (2, 1)]);
+    assert!(book.lookup(&GameState::new()).is_some());
+}
+
+/// Resigns, reports a search and takes every draw, so that it is plain when
+/// it is asked.
+struct Fallback {}
+
+impl Actor for Fallback {
+    fn get_decision(&mut self, _game_state: &GameState, _context: &Context) -> Result<Decision, ActorError> {
+        Ok(Decision::Resign)
+    }
+
+    fn search_report(&self) -> Option<SearchReport> {
+        Some(SearchReport { simulations: 1, expected: 0.5, proven: None })
+    }
+
+    fn accept_draw(&mut self, _game_state: &GameState, _context: &Context) -> Result<bool, ActorError> {
+        Ok(true)
+    }
+}
+
+#[test]
+fn book_actors_leave_positions_outside_the_book_to_the_fallback() {
+    let mut actor = BookActor::new(book(), Box::new(Fallback {}));
+    let in_book = GameState::new();
+    let out_of_book = after(&[Action::new(1, 1, 0, 0, true)]);
+
+    let record = GameRecord::new(in_book.clone());
+    let context = Context::new(&record);
+    assert!(matches!(actor.get_decision(&in_book, &context), Ok(Decision::Move(_))));
+    assert!(actor.search_report().is_none());
+    assert_eq!(actor.accept_draw(&in_book, &context), Ok(false));
+
+    let record = GameRecord::new(out_of_book.clone());
+    let context = Context::new(&record);
+    assert_eq!(actor.get_decision(&out_of_book, &context), Ok(Decision::Resign));
+    assert!(actor.search_report().is_some());
+    assert_eq!(actor.accept_draw(&out_of_book, &context), Ok(true));
+}
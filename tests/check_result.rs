@@ -0,0 +1,170 @@
+use ultimate_ttt::game::board::{BoardStructure, Owned, Square, SubBoard, LINES};
+use ultimate_ttt::game::game_result::GameResult;
+use ultimate_ttt::game::notation::from_notation;
+use ultimate_ttt::game::rules::Rules;
+
+const PLAYERS: [GameResult; 2] = [GameResult::Player1Wins, GameResult::Player2Wins];
+
+// A full 3x3 grid without a line, by rows.
+const DRAWN: [[GameResult; 3]; 3] = {
+    use GameResult::{Player1Wins as O, Player2Wins as X};
+    [[O, X, O], [O, X, X], [X, O, O]]
+};
+
+fn micro() -> BoardStructure<Square> {
+    <BoardStructure<Square>>::new()
+}
+
+fn macro_board() -> BoardStructure<SubBoard> {
+    <BoardStructure<SubBoard>>::new()
+}
+
+fn fill_line<T: Owned>(structure: &mut BoardStructure<T>, line: &[(usize, usize); 3], results: [GameResult; 3]) {
+    for (&(x, y), &result) in line.iter().zip(results.iter()) {
+        structure.set_result_at(x, y, Some(result));
+    }
+}
+
+fn check_every_line<T: Owned>(new: fn() -> BoardStructure<T>) {
+    for line in LINES.iter() {
+        for &player in PLAYERS.iter() {
+            for &(last_x, last_y) in line.iter() {
+                let mut structure = new();
+                fill_line(&mut structure, line, [player; 3]);
+
+                assert_eq!(structure.check_result(last_x, last_y, &Rules::new()), Some(player), "line {:?}, last move ({}, {})", line, last_x, last_y);
+                assert_eq!(structure.full_result(&Rules::new()), Some(player), "line {:?}", line);
+            }
+        }
+    }
+}
+
+fn check_incomplete_lines<T: Owned>(new: fn() -> BoardStructure<T>) {
+    for line in LINES.iter() {
+        for &player in PLAYERS.iter() {
+            for missing in 0..3 {
+                let mut structure = new();
+                for (index, &(x, y)) in line.iter().enumerate() {
+                    if index != missing {
+                        structure.set_result_at(x, y, Some(player));
+                    }
+                }
+
+                for (index, &(last_x, last_y)) in line.iter().enumerate() {
+                    if index != missing {
+                        assert_eq!(structure.check_result(last_x, last_y, &Rules::new()), None, "line {:?}", line);
+                    }
+                }
+                assert_eq!(structure.full_result(&Rules::new()), None, "line {:?}", line);
+            }
+
+            let other = if player == GameResult::Player1Wins { GameResult::Player2Wins } else { GameResult::Player1Wins };
+            for mixed in 0..3 {
+                let mut results = [player; 3];
+                results[mixed] = other;
+
+                let mut structure = new();
+                fill_line(&mut structure, line, results);
+
+                for &(last_x, last_y) in line.iter() {
+                    assert_eq!(structure.check_result(last_x, last_y, &Rules::new()), None, "line {:?}", line);
+                }
+                assert_eq!(structure.full_result(&Rules::new()), None, "line {:?}", line);
+            }
+        }
+    }
+}
+
+fn check_full_without_line<T: Owned>(new: fn() -> BoardStructure<T>) {
+    let mut structure = new();
+    for (y, row) in DRAWN.iter().enumerate() {
+        for (x, &result) in row.iter().enumerate() {
+            structure.set_result_at(x, y, Some(result));
+        }
+    }
+
+    for y in 0..3 {
+        for x in 0..3 {
+            assert_eq!(structure.check_result(x, y, &Rules::new()), Some(GameResult::Draw), "last move ({}, {})", x, y);
+        }
+    }
+    assert_eq!(structure.full_result(&Rules::new()), Some(GameResult::Draw));
+}
+
+#[test]
+fn every_micro_line_wins() {
+    check_every_line(micro);
+}
+
+#[test]
+fn every_macro_line_wins() {
+    check_every_line(macro_board);
+}
+
+#[test]
+fn incomplete_or_mixed_micro_lines_do_not_win() {
+    check_incomplete_lines(micro);
+}
+
+#[test]
+fn incomplete_or_mixed_macro_lines_do_not_win() {
+    check_incomplete_lines(macro_board);
+}
+
+#[test]
+fn full_micro_board_without_line_is_drawn() {
+    check_full_without_line(micro);
+}
+
+#[test]
+fn full_macro_board_without_line_is_drawn() {
+    check_full_without_line(macro_board);
+}
+
+#[test]
+fn drawn_sub_boards_never_complete_a_line() {
+    for line in LINES.iter() {
+        for &player in PLAYERS.iter() {
+            for draws in 1..8 {
+                let mut results = [player; 3];
+                for (index, result) in results.iter_mut().enumerate() {
+                    if draws & (1 << index) != 0 {
+                        *result = GameResult::Draw;
+                    }
+                }
+
+                let mut structure = macro_board();
+                fill_line(&mut structure, line, results);
+
+                for &(last_x, last_y) in line.iter() {
+                    assert_eq!(structure.check_result(last_x, last_y, &Rules::new()), None, "line {:?}, results {:?}", line, results);
+                }
+                assert_eq!(structure.full_result(&Rules::new()), None, "line {:?}, results {:?}", line, results);
+            }
+        }
+    }
+}
+
+#[test]
+fn drawing_third_sub_board_in_a_line_does_not_end_game() {
+    // The two top-left sub-boards are drawn and player 1 fills the last square
+    // of the top-right one without a line.
+    let mut game_state = from_notation(
+        "OXOOXOOXO/OXXOXXOXX/XOOXOOXO./..X....../....X..../........./........./........./......... 1 2"
+    ).unwrap();
+
+    assert_eq!(game_state.make_move(2, 0, 2, 2), None);
+    assert_eq!(game_state.board().get(2, 0).result(), Some(GameResult::Draw));
+    assert_eq!(game_state.possible_actions().len(), 9);
+}
+
+#[test]
+fn closing_every_sub_board_without_a_line_draws_the_game() {
+    // Every sub-board but the bottom-right one has been won, without a line on
+    // the macro board; player 1 draws the last one.
+    let mut game_state = from_notation(
+        "OOOXXXOOO/........./........./OOOXXXXXX/........./........./...OOOOXO/......OXX/XXX...XO. 1 8"
+    ).unwrap();
+
+    assert_eq!(game_state.make_move(2, 2, 2, 2), Some(GameResult::Draw));
+}
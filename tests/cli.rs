@@ -0,0 +1,110 @@
+use ultimate_ttt::cli::{parse, ActorKind, Command};
+use ultimate_ttt::game::notation::to_notation;
+use ultimate_ttt::gui::Starter;
+
+fn args(line: &str) -> Vec<String> {
+    line.split_whitespace().map(|arg| arg.to_string()).collect()
+}
+
+#[test]
+fn defaults_to_playing_against_mcts() {
+    let options = parse(&[]).unwrap();
+
+    assert_eq!(options.command, Command::Play);
+    assert_eq!(options.first, ActorKind::Human);
+    assert_eq!(options.second, ActorKind::Mcts);
+    assert_eq!(options.starter, Starter::First);
+    assert_eq!(options.time, 1000);
+}
+
+#[test]
+fn parses_subcommands_and_options() {
+    let options = parse(&args("match --first heuristic --second mcts --games 4 --iterations 500 --threads 2 --seed 7 --output games.txt")).unwrap();
+
+    assert_eq!(options.command, Command::Match);
+    assert_eq!(options.first, ActorKind::Heuristic);
+    assert_eq!(options.games, 4);
+    assert_eq!(options.iterations, Some(500));
+    assert_eq!(options.threads, 2);
+    assert_eq!(options.seed, Some(7));
+    assert_eq!(options.output.as_deref(), Some("games.txt"));
+
+    let options = parse(&args("play --starter random --second mcts --plain --unicode")).unwrap();
+    assert_eq!(options.starter, Starter::Random);
+    assert!(options.plain && options.unicode);
+    assert_eq!(parse(&args("play --first mcts --delay 500")).unwrap().delay, 500);
+
+    assert_eq!(parse(&args("analyse")).unwrap().command, Command::Analyse);
+    assert_eq!(parse(&args("selfplay --games 2")).unwrap().command, Command::SelfPlay);
+    assert_eq!(parse(&args("play --help")).unwrap().command, Command::Help);
+
+    let options = parse(&args("match --parallel 4 --openings 2 --sprt 0,10 --second-time 50 --second-iterations 100")).unwrap();
+    assert_eq!((options.parallel, options.openings), (4, 2));
+    assert_eq!(options.sprt, Some((0., 10.)));
+    assert_eq!((options.second_time, options.second_iterations), (Some(50), Some(100)));
+
+    let options = parse(&args("review --input game.txt")).unwrap();
+    assert_eq!(options.command, Command::Review);
+    assert_eq!(options.input.as_deref(), Some("game.txt"));
+}
+
+#[test]
+fn parses_positions() {
+    let notation = "........./........./........./........./....O..../........./........./........./......... 2 4";
+    let mut arguments = args("analyse --position");
+    arguments.push(notation.to_string());
+
+    assert_eq!(to_notation(&parse(&arguments).unwrap().position), notation);
+}
+
+#[test]
+fn rejects_bad_arguments() {
+    assert!(parse(&args("fly")).is_err());
+    assert!(parse(&args("play --time")).is_err());
+    assert!(parse(&args("play --time soon")).is_err());
+    assert!(parse(&args("play --first robot")).is_err());
+    assert!(parse(&args("match --sprt 5")).is_err());
+    assert!(parse(&args("play --first nn")).is_err());
+    assert!(parse(&args("match --second human")).is_err());
+    assert!(parse(&args("play --colour red")).is_err());
+}
+
+#[test]
+fn rejects_decided_positions() {
+    // Player 1 has won the whole top row of sub-boards.
+    let notation = "OOOOOOOOO/XX.XX.XX./........./........./........./........./........./........./......... 2 -";
+
+    for command in ["analyse", "selfplay", "match", "play"].iter() {
+        let mut arguments = args(&format!("{} --first mcts --position", command));
+        arguments.push(notation.to_string());
+        assert!(parse(&arguments).err().unwrap().contains("decided"), "{}", command);
+    }
+}
+
+#[test]
+fn networks_only_play_on_the_standard_board() {
+    let notation = vec![".".repeat(16); 16].join("/") + " 1 -";
+    let mut arguments = args("analyse --first nn --network weights.bin --position");
+    arguments.push(notation.clone());
+    assert!(parse(&arguments).err().unwrap().contains("standard board"));
+
+    let mut arguments = args("analyse --position");
+    arguments.push(notation);
+    let mut options = parse(&arguments).unwrap();
+    options.network = Some("weights.bin".to_string());
+    assert!(options.actor(ActorKind::Network).err().unwrap().contains("standard board"));
+}
+
+#[test]
+fn selfplay_only_records_the_standard_board() {
+    let notation = vec![".".repeat(16); 16].join("/") + " 1 -";
+    let mut arguments = args("selfplay --output games.txt --position");
+    arguments.push(notation);
+    assert!(parse(&arguments).err().unwrap().contains("standard board"));
+}
+
+#[test]
+fn openings_must_leave_the_game_open() {
+    assert!(parse(&args("match --openings 80")).is_ok());
+    assert!(parse(&args("match --openings 81")).err().unwrap().contains("--openings"));
+}
@@ -0,0 +1,56 @@
+use ultimate_ttt::ai::endgame::{continuations, EndgameSolver};
+use ultimate_ttt::game::action::Action;
+use ultimate_ttt::game::game_result::GameResult;
+use ultimate_ttt::game::game_state::GameState;
+use ultimate_ttt::game::notation::from_notation;
+
+/// Player 1 has won the top-left and top-middle sub-boards, every other one
+/// but the top-right is drawn, and the top-right has two empty squares: the
+/// top-middle one completes player 1's line and the middle-right one player
+/// 2's. Whoever moves is sent to the top-right.
+fn position(player: usize) -> GameState {
+    let notation = format!("OOOOOOO.O/XXOXXOXX./XOXXOXXOX/OXOOXOOXO/OXXOXXOXX/XOOXOOXOO/OXOOXOOXO/OXXOXXOXX/XOOXOOXOO {} 2", player);
+    from_notation(&notation).unwrap()
+}
+
+#[test]
+fn the_solver_finds_the_winning_move() {
+    let mut solver = EndgameSolver::new();
+    let (action, result) = solver.solve(&position(1)).unwrap();
+
+    assert_eq!(result, GameResult::Player1Wins);
+    assert_eq!(action, Action::new(2, 0, 1, 0, false));
+}
+
+#[test]
+fn the_solver_sees_the_draw() {
+    let mut solver = EndgameSolver::new();
+    let (action, result) = solver.solve(&position(2)).unwrap();
+
+    assert_eq!(result, GameResult::Draw);
+    assert!(position(2).possible_actions().contains(&action));
+}
+
+#[test]
+fn the_solver_has_nothing_to_solve_once_every_sub_board_is_closed() {
+    let notation = "OOOOOOOXO/XXOXXOXXO/XOXXOXXOX/OXOOXOOXO/OXXOXXOXX/XOOXOOXOO/OXOOXOOXO/OXXOXXOXX/XOOXOOXOO 1 -";
+    let game_state = from_notation(notation).unwrap();
+    assert!(game_state.possible_actions().is_empty());
+
+    assert!(EndgameSolver::new().solve(&game_state).is_none());
+}
+
+#[test]
+fn the_solver_gives_up_when_told_to_stop() {
+    let mut solver = EndgameSolver::new();
+
+    assert!(solver.solve_until(&GameState::new(), &|| true).is_none());
+    assert!(solver.solve_until(&position(1), &|| true).is_some());
+}
+
+#[test]
+fn continuations_are_counted_up_to_the_limit() {
+    assert_eq!(continuations(&position(1), 100), 2);
+    assert_eq!(continuations(&position(2), 100), 2);
+    assert_eq!(continuations(&GameState::new(), 1000), 1001);
+}
@@ -0,0 +1,58 @@
+use ultimate_ttt::game::action::Action;
+use ultimate_ttt::game::game_state::GameState;
+use ultimate_ttt::game::notation::from_notation;
+use ultimate_ttt::game::rules::Rules;
+use ultimate_ttt::gui::input::{parse, Input};
+
+fn forced() -> GameState {
+    from_notation("........./........./........./........./....O..../........./........./........./......... 2 4").unwrap()
+}
+
+#[test]
+fn moves_in_every_format() {
+    let game_state = GameState::new();
+    let expected = Input::Move(Action::new(1, 2, 0, 1, true));
+
+    for line in ["1201", "1 2 0 1", "1,2,0,1", " 1, 2, 0, 1 \n", "d8", "D8"].iter() {
+        assert_eq!(parse(line, &game_state), Ok(expected.clone()), "{:?}", line);
+    }
+
+    let expected = Input::Move(Action::new(1, 1, 2, 0, false));
+    for line in ["20", "2 0", "1120", "f4"].iter() {
+        assert_eq!(parse(line, &forced()), Ok(expected.clone()), "{:?}", line);
+    }
+}
+
+#[test]
+fn grid_coordinates_follow_the_board_size() {
+    let game_state = GameState::with_rules(Rules::new().with_size(4, 3));
+    assert_eq!(parse("p16", &game_state), Ok(Input::Move(Action::new(3, 3, 3, 3, true))));
+    assert_eq!(parse("e1", &game_state), Ok(Input::Move(Action::new(1, 0, 0, 0, true))));
+    assert!(parse("q1", &game_state).is_err());
+}
+
+#[test]
+fn commands() {
+    let game_state = GameState::new();
+
+    assert_eq!(parse("undo", &game_state), Ok(Input::Undo));
+    assert_eq!(parse("HINT", &game_state), Ok(Input::Hint));
+    assert_eq!(parse("resign", &game_state), Ok(Input::Resign));
+    assert_eq!(parse("draw", &game_state), Ok(Input::OfferDraw));
+    assert_eq!(parse("save My Game.txt", &game_state), Ok(Input::Save("My Game.txt".to_string())));
+    assert_eq!(parse("show  moves", &game_state), Ok(Input::ShowMoves));
+    assert_eq!(parse("quit", &game_state), Ok(Input::Quit));
+    assert_eq!(parse("help", &game_state), Ok(Input::Help));
+}
+
+#[test]
+fn invalid_and_illegal_input_is_explained() {
+    let game_state = GameState::new();
+
+    for line in ["", "save", "hello", "123", "12345", "1 2 0 x", "3300", "a0", "j1", "20"].iter() {
+        assert!(parse(line, &game_state).is_err(), "{:?}", line);
+    }
+
+    assert_eq!(parse("0000", &forced()), Err("You have to play in sub-board 1 1.".to_string()));
+    assert_eq!(parse("11", &forced()), Err("That square is not available.".to_string()));
+}
@@ -0,0 +1,169 @@
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use ultimate_ttt::game::board::{Owned, SubBoard, LINES};
+use ultimate_ttt::game::game_result::GameResult;
+use ultimate_ttt::game::game_state::GameState;
+use ultimate_ttt::game::player::Player;
+
+const GAMES: u64 = 500;
+
+/// The standard rules for a 3x3 grid of results, written down independently
+/// of the engine: the grid goes to the player who owns all three cells of one
+/// of its lines, a drawn sub-board belonging to neither player, and is drawn
+/// once every cell is decided without such a line.
+fn brute_force<F: Fn(usize, usize) -> Option<GameResult>>(get: F) -> Option<GameResult> {
+    for line in LINES.iter() {
+        for &player in [GameResult::Player1Wins, GameResult::Player2Wins].iter() {
+            if line.iter().all(|&(x, y)| get(x, y) == Some(player)) {
+                return Some(player);
+            }
+        }
+    }
+
+    let full = (0..3).all(|y| (0..3).all(|x| get(x, y).is_some()));
+    if full { Some(GameResult::Draw) } else { None }
+}
+
+/// A sub-board's result, from its squares alone.
+fn brute_force_sub_board(sub_board: &SubBoard) -> Option<GameResult> {
+    brute_force(|x, y| sub_board.get(x, y).result())
+}
+
+/// The game's result, from the squares alone: the sub-boards' results are
+/// worked out again rather than read from the board.
+fn brute_force_game(game_state: &GameState) -> Option<GameResult> {
+    brute_force(|x, y| brute_force_sub_board(game_state.board().get(x, y)))
+}
+
+fn count(game_state: &GameState, result: GameResult) -> usize {
+    game_state.board().structure().items().iter()
+        .map(|sub_board| sub_board.structure().items().iter().filter(|square| square.result() == Some(result)).count())
+        .sum()
+}
+
+fn check_counts(game_state: &GameState, seed: u64) {
+    let player1 = count(game_state, GameResult::Player1Wins);
+    let player2 = count(game_state, GameResult::Player2Wins);
+
+    match game_state.current_player() {
+        Player::Player1 => assert_eq!(player1, player2, "seed {}", seed),
+        Player::Player2 => assert_eq!(player1, player2 + 1, "seed {}", seed),
+    }
+}
+
+fn check_sub_boards(game_state: &GameState, seed: u64) {
+    for sub_y in 0..3 {
+        for sub_x in 0..3 {
+            let sub_board = game_state.board().get(sub_x, sub_y);
+            assert_eq!(sub_board.result(), brute_force_sub_board(sub_board), "seed {}, sub-board ({}, {})", seed, sub_x, sub_y);
+        }
+    }
+}
+
+fn check_actions(game_state: &GameState, seed: u64) {
+    let actions = game_state.possible_actions();
+    assert!(!actions.is_empty(), "seed {}: no moves in an unfinished game", seed);
+
+    for action in actions.iter() {
+        let sub_board = game_state.board().get(action.sub_x, action.sub_y);
+        assert!(sub_board.result().is_none(), "seed {}: move in a closed sub-board", seed);
+        assert!(sub_board.get(action.x, action.y).result().is_none(), "seed {}: move on an occupied square", seed);
+        assert_eq!(action.full_board, game_state.current_sub_x.is_none(), "seed {}", seed);
+
+        if let (Some(sub_x), Some(sub_y)) = (game_state.current_sub_x, game_state.current_sub_y) {
+            assert_eq!((action.sub_x, action.sub_y), (sub_x, sub_y), "seed {}: move outside the forced sub-board", seed);
+        }
+    }
+
+    if game_state.current_sub_x.is_none() {
+        let expected: usize = game_state.board().structure().items().iter()
+            .filter(|sub_board| sub_board.result().is_none())
+            .map(|sub_board| sub_board.structure().items().iter().filter(|square| square.result().is_none()).count())
+            .sum();
+        assert_eq!(actions.len(), expected, "seed {}: not every free square is offered", seed);
+    }
+}
+
+fn check_initial_actions(game_state: &GameState, seed: u64) {
+    let actions = game_state.initialize_actions();
+
+    for sub_y in 0..3 {
+        for sub_x in 0..3 {
+            let sub_board = game_state.board().get(sub_x, sub_y);
+            let mut expected: Vec<(usize, usize)> = (0..9)
+                .map(|i| (i % 3, i / 3))
+                .filter(|&(x, y)| sub_board.get(x, y).result().is_none())
+                .collect();
+            let mut found: Vec<(usize, usize)> = actions[3 * sub_y + sub_x].iter()
+                .inspect(|action| assert_eq!((action.sub_x, action.sub_y), (sub_x, sub_y), "seed {}", seed))
+                .map(|action| (action.x, action.y))
+                .collect();
+
+            expected.sort();
+            found.sort();
+            assert_eq!(found, expected, "seed {}, sub-board ({}, {})", seed, sub_x, sub_y);
+        }
+    }
+}
+
+#[test]
+fn random_games_respect_invariants() {
+    for seed in 0..GAMES {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut game_state = GameState::new();
+
+        loop {
+            check_counts(&game_state, seed);
+            check_sub_boards(&game_state, seed);
+            check_actions(&game_state, seed);
+            check_initial_actions(&game_state, seed);
+            assert_eq!(brute_force_game(&game_state), None, "seed {}: game should be over", seed);
+
+            let player = game_state.current_player();
+            let action = game_state.possible_actions().choose(&mut rng).unwrap().clone();
+            let result = action.apply(&mut game_state);
+
+            assert_eq!(game_state.current_player(), player.next(), "seed {}", seed);
+            assert_eq!(result, brute_force_game(&game_state), "seed {}", seed);
+
+            if let Some(result) = result {
+                check_counts(&game_state, seed);
+                check_sub_boards(&game_state, seed);
+                if result != GameResult::Draw {
+                    assert_eq!(result, player.wins(), "seed {}: only the player who moved can win", seed);
+                }
+                break;
+            }
+        }
+    }
+}
+
+#[test]
+fn random_playouts_end_consistently() {
+    for seed in 0..GAMES {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut game_state = GameState::new();
+
+        for _ in 0..(seed % 20) {
+            let action = game_state.possible_actions().choose(&mut rng).unwrap().clone();
+            if action.apply(&mut game_state).is_some() {
+                break;
+            }
+        }
+
+        if brute_force_game(&game_state).is_some() {
+            continue;
+        }
+
+        let (final_state, result) = game_state.play_out(&mut rng);
+
+        check_counts(&final_state, seed);
+        check_sub_boards(&final_state, seed);
+        assert_eq!(Some(result), brute_force_game(&final_state), "seed {}", seed);
+        if result != GameResult::Draw {
+            assert_eq!(result, final_state.current_player().next().wins(), "seed {}", seed);
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use std::sync::Mutex;
+
+use ultimate_ttt::ai::evaluator::Evaluator;
+use ultimate_ttt::ai::mcts::{mcts_rec, search, search_from, search_parallel, search_parallel_from, Limit, Node};
+use ultimate_ttt::game::game_state::GameState;
+use ultimate_ttt::game::player::Player;
+
+/// Remembers every position it is asked about.
+struct Recorder(Mutex<Vec<GameState>>);
+
+impl Evaluator for Recorder {
+    fn evaluate(&self, game_state: &GameState) -> f32 {
+        self.0.lock().unwrap().push(game_state.clone());
+        0.5
+    }
+}
+
+#[test]
+fn leaves_are_evaluated_after_their_move() {
+    let recorder = Recorder(Mutex::new(Vec::new()));
+    let mut root = Node::new(GameState::new(), None);
+    mcts_rec(&mut root, Some(&recorder));
+
+    let evaluated = recorder.0.into_inner().unwrap();
+    assert_eq!(evaluated.len(), 1);
+    assert!(evaluated[0].current_player() == Player::Player2);
+    assert!(evaluated[0].possible_actions().len() < GameState::new().possible_actions().len());
+    assert_eq!(root.children()[0].weight(), 0.5);
+}
+
+#[test]
+fn iteration_limit_is_exact() {
+    let (root, count) = search(&GameState::new(), Limit::Iterations(300), None);
+
+    assert_eq!(count, 300);
+    assert_eq!(root.visits(), 300);
+}
+
+#[test]
+fn parallel_search_combines_the_root_moves() {
+    let game_state = GameState::new();
+    let (root, count) = search_parallel(&game_state, Limit::Iterations(400), 4, None);
+
+    assert_eq!(count, 400);
+    assert_eq!(root.visits(), 400);
+    assert_eq!(root.children().len(), game_state.possible_actions().len());
+    assert_eq!(root.children().iter().map(|child| child.visits()).sum::<usize>(), 400);
+
+    let share: f32 = root.visit_distribution().iter().map(|(_, share)| share).sum();
+    assert!((share - 1.).abs() < 1e-4);
+}
+
+/// Checks that every node below `node` counts the simulations through it: its
+/// children's, and the one that expanded it in each of at most `searches`
+/// merged searches.
+fn check_visits(node: &Node, searches: usize) {
+    let below: usize = node.children().iter().map(|child| child.visits()).sum();
+    if node.children().is_empty() {
+        return;
+    }
+
+    assert!(node.visits() > below && node.visits() - below <= searches, "{} visits, {} below", node.visits(), below);
+    for child in node.children() {
+        check_visits(child, searches);
+    }
+}
+
+#[test]
+fn parallel_search_merges_whole_trees() {
+    let (root, _) = search_parallel(&GameState::new(), Limit::Iterations(2000), 4, None);
+    for child in root.children() {
+        check_visits(child, 4);
+    }
+
+    let (action, _) = root.best_action();
+    let (_, reply, _) = root.move_statistics().into_iter().find(|(other, _, _)| *other == action).unwrap();
+    let subtree = root.into_child(&action).unwrap();
+    assert_eq!(subtree.visits(), reply);
+
+    let (subtree, _) = search_parallel_from(subtree, Limit::Iterations(400), 4, None, &|| false);
+    assert_eq!(subtree.visits(), reply + 400);
+    for child in subtree.children() {
+        check_visits(child, 8);
+    }
+}
+
+#[test]
+fn searches_go_on_from_the_subtree_of_a_move() {
+    let (root, _) = search(&GameState::new(), Limit::Iterations(2000), None);
+    let (action, _) = root.best_action();
+    let (_, reply, _) = root.move_statistics().into_iter().find(|(other, _, _)| *other == action).unwrap();
+
+    let mut game_state = GameState::new();
+    action.apply(&mut game_state);
+    let subtree = root.into_child(&action).unwrap();
+    assert!(*subtree.state() == game_state);
+    assert_eq!(subtree.visits(), reply);
+
+    let (subtree, count) = search_from(subtree, Limit::Iterations(100), None, &|| false);
+    assert_eq!(count, 100);
+    assert_eq!(subtree.visits(), reply + 100);
+
+    let (subtree, count) = search_parallel_from(subtree, Limit::Iterations(100), 2, None, &|| false);
+    assert_eq!(count, 100);
+    assert_eq!(subtree.visits(), reply + 200);
+}
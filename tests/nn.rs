@@ -0,0 +1,122 @@
+use std::io::ErrorKind;
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use ultimate_ttt::actor::{Actor, ActorError, CancelToken, Clock, Context, Decision};
+use ultimate_ttt::ai::nn::{cell_index, encode, Network, CELLS, INPUT_SIZE};
+use ultimate_ttt::ai::puct::PUCTActor;
+use ultimate_ttt::game::action::Action;
+use ultimate_ttt::game::game_state::GameState;
+use ultimate_ttt::game::record::GameRecord;
+
+fn network() -> Network {
+    Network::random(&[16, 8], &mut StdRng::seed_from_u64(1))
+}
+
+/// The move the actor decides on, asked outside any game.
+fn decide(actor: &mut dyn Actor, game_state: &GameState) -> Action {
+    let record = GameRecord::new(game_state.clone());
+    match actor.get_decision(game_state, &Context::new(&record)) {
+        Ok(Decision::Move(action)) => action,
+        other => panic!("expected a move, got {:?}", other),
+    }
+}
+
+#[test]
+fn networks_survive_a_round_trip() {
+    let network = network();
+    let mut bytes = Vec::new();
+    network.write(&mut bytes).unwrap();
+
+    let read = Network::read(&mut bytes.as_slice()).unwrap();
+    assert_eq!(read.hidden_sizes(), vec![16, 8]);
+
+    let input = encode(&GameState::new());
+    assert_eq!(read.forward(&input), network.forward(&input));
+}
+
+#[test]
+fn other_files_are_not_networks() {
+    assert!(Network::read(&mut &b"UTBK\x01\0\0\0"[..]).is_err());
+    assert!(Network::read(&mut &b"UTNN"[..]).is_err());
+}
+
+#[test]
+fn corrupt_headers_are_rejected() {
+    let header = |layers: u32, sizes: &[u32]| {
+        let mut bytes = b"UTNN\x01\0\0\0".to_vec();
+        bytes.extend_from_slice(&layers.to_le_bytes());
+        for size in sizes {
+            bytes.extend_from_slice(&size.to_le_bytes());
+        }
+        bytes
+    };
+    let kind = |bytes: Vec<u8>| Network::read(&mut bytes.as_slice()).err().unwrap().kind();
+
+    assert_eq!(kind(header(u32::MAX, &[])), ErrorKind::InvalidData);
+    assert_eq!(kind(header(1, &[u32::MAX])), ErrorKind::InvalidData);
+    assert_eq!(kind(header(1, &[0])), ErrorKind::InvalidData);
+    assert_eq!(kind(header(1, &[16])), ErrorKind::UnexpectedEof);
+
+    let mut bytes = Vec::new();
+    network().write(&mut bytes).unwrap();
+    bytes.truncate(bytes.len() - 1);
+    assert_eq!(kind(bytes), ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn positions_are_encoded_for_the_player_to_move() {
+    let mut game_state = GameState::new();
+    let action = Action::new(1, 1, 0, 0, true);
+    action.apply(&mut game_state);
+
+    let input = encode(&game_state);
+    assert_eq!(input.len(), INPUT_SIZE);
+
+    let plane = |plane: usize| &input[plane * CELLS..(plane + 1) * CELLS];
+    assert!(plane(0).iter().all(|&value| value == 0.));
+    assert_eq!(plane(1).iter().sum::<f32>(), 1.);
+    assert_eq!(plane(1)[cell_index(&action)], 1.);
+
+    assert_eq!(plane(2).iter().sum::<f32>(), 9.);
+    for action in game_state.possible_actions() {
+        assert_eq!(plane(2)[cell_index(&action)], 1.);
+    }
+    assert!(plane(3).iter().all(|&value| value == 0.));
+}
+
+#[test]
+fn puct_plays_legal_moves() {
+    let mut actor = PUCTActor::new(network(), 0);
+    let mut game_state = GameState::new();
+
+    loop {
+        let action = decide(&mut actor, &game_state);
+        assert!(game_state.possible_actions().iter().any(|legal| cell_index(legal) == cell_index(&action)));
+
+        if action.apply(&mut game_state).is_some() {
+            break;
+        }
+    }
+}
+
+#[test]
+fn puct_stops_for_the_clock_and_cancellation() {
+    let mut actor = PUCTActor::new(network(), 60_000);
+    let game_state = GameState::new();
+    let record = GameRecord::new(game_state.clone());
+
+    let start = Instant::now();
+    let context = Context::new(&record).with_clock(Clock::new(Some(Duration::from_millis(50))));
+    assert!(matches!(actor.get_decision(&game_state, &context), Ok(Decision::Move(_))));
+    assert!(actor.search_report().is_some());
+    assert!(start.elapsed() < Duration::from_secs(10));
+
+    let cancel = CancelToken::new();
+    cancel.cancel();
+    let context = Context::new(&record).with_cancel(cancel);
+    assert_eq!(actor.get_decision(&game_state, &context), Err(ActorError::Cancelled));
+    assert!(start.elapsed() < Duration::from_secs(10));
+}
@@ -0,0 +1,61 @@
+use ultimate_ttt::game::game_state::GameState;
+use ultimate_ttt::game::notation::from_notation;
+
+fn check(game_state: &GameState, expected: &[u64]) {
+    for (depth, &count) in expected.iter().enumerate() {
+        assert_eq!(game_state.perft(depth + 1), count, "perft({})", depth + 1);
+    }
+}
+
+fn position(notation: &str) -> GameState {
+    from_notation(notation).unwrap()
+}
+
+#[test]
+fn perft_zero_is_one() {
+    assert_eq!(GameState::new().perft(0), 1);
+}
+
+#[test]
+fn start_position() {
+    check(&GameState::new(), &[81, 720, 6336, 55080, 473256]);
+}
+
+#[test]
+fn sent_to_won_sub_board() {
+    // Player 2's last move sends player 1 to the top-left sub-board, which
+    // player 1 has already won, so they may play anywhere else.
+    check(
+        &position("OOO....../...X...../........./........./....X..../........./......X../........./......... 1 -"),
+        &[69, 1004, 14061, 189818],
+    );
+}
+
+#[test]
+fn sent_to_drawn_sub_board() {
+    // The central sub-board is full without a winner.
+    check(
+        &position("........./.X......./........./...OXO.../...OXX.../...XOO.../........./........./......... 1 -"),
+        &[71, 1050, 14987, 206374],
+    );
+}
+
+#[test]
+fn forced_into_last_square() {
+    // The only move fills and wins the top-right sub-board and sends the
+    // opponent to the empty bottom-right one.
+    check(
+        &position("..X...OXO/......XXO/......OO./........./....X..../........./........./........./......... 1 2"),
+        &[1, 9, 138, 1951],
+    );
+}
+
+#[test]
+fn winning_move_ends_game() {
+    // Player 1 owns the top-left and central sub-boards and can take the
+    // bottom-right one; games that end are not expanded any further.
+    check(
+        &position("OOO....../...X.X.../........./X..X...../...OOO.../..X.....X/......OO./X......../...X..... 1 8"),
+        &[7, 90, 1506, 24656],
+    );
+}
@@ -0,0 +1,172 @@
+use ultimate_ttt::actor::{Actor, ActorError, Context, Decision};
+use ultimate_ttt::game::game_result::GameResult::{Draw, Player2Wins};
+use ultimate_ttt::game::game_state::GameState;
+use ultimate_ttt::game::notation::{from_notation, to_notation};
+use ultimate_ttt::game::player::{Player, PlayerConfig};
+use ultimate_ttt::game::record::GameRecord;
+use ultimate_ttt::game::rules::{Rules, WinCondition};
+use ultimate_ttt::gui::{self, Match, Setup, Starter};
+
+/// Always plays the first legal move; the cell it picks reveals which side it
+/// plays.
+struct FirstMove {}
+
+impl Actor for FirstMove {
+    fn get_decision(&mut self, game_state: &GameState, _context: &Context) -> Result<Decision, ActorError> {
+        Ok(Decision::Move(game_state.possible_actions()[0].clone()))
+    }
+}
+
+/// Always plays the last legal move.
+struct LastMove {}
+
+impl Actor for LastMove {
+    fn get_decision(&mut self, game_state: &GameState, _context: &Context) -> Result<Decision, ActorError> {
+        Ok(Decision::Move(game_state.possible_actions().pop().unwrap()))
+    }
+}
+
+/// Makes the given decisions in order, then plays like `FirstMove`.
+struct Scripted {
+    decisions: Vec<Decision>,
+}
+
+impl Scripted {
+    fn new(mut decisions: Vec<Decision>) -> Scripted {
+        decisions.reverse();
+        Scripted { decisions }
+    }
+}
+
+impl Actor for Scripted {
+    fn get_decision(&mut self, game_state: &GameState, context: &Context) -> Result<Decision, ActorError> {
+        match self.decisions.pop() {
+            Some(decision) => Ok(decision),
+            None => FirstMove {}.get_decision(game_state, context),
+        }
+    }
+}
+
+fn setup() -> Setup {
+    Setup::new().with_players(PlayerConfig::new("First", 'A'), PlayerConfig::new("Second", 'B'))
+}
+
+#[test]
+fn starter_decides_who_plays_player_1() {
+    let record = gui::play(&setup().with_starter(Starter::First), &mut FirstMove {}, &mut LastMove {});
+    assert_eq!(record.player(Player::Player1).name, "First");
+    assert_eq!(record.player(Player::Player2).symbol, 'B');
+    assert_eq!(record.moves[0], GameState::new().possible_actions()[0]);
+
+    let record = gui::play(&setup().with_starter(Starter::Second), &mut FirstMove {}, &mut LastMove {});
+    assert_eq!(record.player(Player::Player1).name, "Second");
+    assert_eq!(record.player(Player::Player2).name, "First");
+    assert_eq!(record.moves[0], GameState::new().possible_actions().pop().unwrap());
+}
+
+#[test]
+fn starter_moves_first_when_player_2_is_to_move() {
+    let initial_state = from_notation("........./........./........./........./....O..../........./........./........./......... 2 4").unwrap();
+    let setup = setup().with_starter(Starter::First).with_initial_state(initial_state.clone());
+
+    let record = gui::play(&setup, &mut FirstMove {}, &mut LastMove {});
+    assert_eq!(record.player(Player::Player2).name, "First");
+    assert_eq!(record.moves[0], initial_state.possible_actions()[0]);
+    assert_eq!(to_notation(&record.initial_state), to_notation(&initial_state));
+}
+
+#[test]
+fn random_starter_seats_both_actors() {
+    let mut first_starts = 0;
+
+    for _ in 0..40 {
+        let record = gui::play(&setup().with_starter(Starter::Random), &mut FirstMove {}, &mut LastMove {});
+        assert_ne!(record.players[0].name, record.players[1].name);
+
+        if record.players[0].name == "First" {
+            first_starts += 1;
+        }
+    }
+
+    assert!(first_starts > 0 && first_starts < 40);
+}
+
+#[test]
+fn record_replays_to_the_result() {
+    let record = gui::play(&setup(), &mut FirstMove {}, &mut LastMove {});
+    let mut game_state = record.initial_state.clone();
+    let mut result = None;
+
+    for action in record.moves.iter() {
+        assert!(result.is_none());
+        result = action.apply(&mut game_state);
+    }
+
+    assert_eq!(result, record.result);
+    assert_eq!(to_notation(&record.final_state()), to_notation(&game_state));
+    assert!(record.describe_result().ends_with("wins!") || record.describe_result() == "Draw!");
+}
+
+#[test]
+fn records_round_trip_through_text() {
+    let rules = Rules::new().with_win_condition(WinCondition::Misere).with_sub_board_tiebreak(true);
+    let setup = setup().with_initial_state(GameState::with_rules(rules));
+    let record = gui::play(&setup, &mut FirstMove {}, &mut LastMove {});
+
+    let parsed = GameRecord::from_text(&record.to_text()).unwrap();
+    assert_eq!(parsed.players, record.players);
+    assert_eq!(parsed.moves, record.moves);
+    assert_eq!(parsed.result, record.result);
+    assert_eq!(*parsed.initial_state.rules(), rules);
+    assert_eq!(parsed.to_text(), record.to_text());
+
+    let mut unfinished = GameRecord::new(GameState::new());
+    unfinished.push(GameState::new().possible_actions()[40].clone(), None);
+    assert!(unfinished.to_text().contains("moves 1111\nresult *"));
+    assert_eq!(GameRecord::from_text(&unfinished.to_text()).unwrap().moves, unfinished.moves);
+}
+
+#[test]
+fn rejects_inconsistent_records() {
+    let record = gui::play(&setup(), &mut FirstMove {}, &mut LastMove {});
+    let text = record.to_text();
+
+    assert!(GameRecord::from_text(&text.replace("result ", "result x")).is_err());
+    let wrong_result = if record.result == Some(Draw) { "result 1" } else { "result =" };
+    assert!(GameRecord::from_text(&text.lines().map(|line| if line.starts_with("result") { wrong_result } else { line }).collect::<Vec<_>>().join("\n")).is_err());
+    assert!(GameRecord::from_text(&text.replace("moves ", "moves 1111 1111 ")).is_err());
+    assert!(GameRecord::from_text(&text.replace("start", "begin")).is_err());
+}
+
+#[test]
+fn undo_takes_back_a_move_and_its_reply() {
+    let start = GameState::new();
+    let mut first = Scripted::new(vec![
+        Decision::Undo,
+        Decision::Move(start.possible_actions()[10].clone()),
+        Decision::Undo,
+        Decision::Move(start.possible_actions()[20].clone()),
+        Decision::Quit,
+    ]);
+
+    let record = Match::new(setup()).play(&mut first, &mut LastMove {});
+    let mut after_first_move = start.clone();
+    start.possible_actions()[20].apply(&mut after_first_move);
+
+    assert_eq!(record.moves, vec![start.possible_actions()[20].clone(), after_first_move.possible_actions().pop().unwrap()]);
+    assert_eq!(record.result, None);
+}
+
+#[test]
+fn resignation_ends_the_game() {
+    let mut first = Scripted::new(vec![Decision::Move(GameState::new().possible_actions()[0].clone()), Decision::Resign]);
+    let record = Match::new(setup()).play(&mut first, &mut LastMove {});
+
+    assert_eq!(record.moves.len(), 2);
+    assert_eq!(record.result, Some(Player2Wins));
+    assert_eq!(record.describe_result(), "Second wins!");
+
+    let parsed = GameRecord::from_text(&record.to_text()).unwrap();
+    assert_eq!(parsed.result, Some(Player2Wins));
+    assert_eq!(parsed.moves, record.moves);
+}
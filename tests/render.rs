@@ -0,0 +1,80 @@
+use ultimate_ttt::game::action::Action;
+use ultimate_ttt::game::game_state::GameState;
+use ultimate_ttt::game::notation::from_notation;
+use ultimate_ttt::gui::render::{render, RenderOptions};
+
+const SYMBOLS: [char; 2] = ['O', 'X'];
+
+/// O has won the top-left sub-board and X the centre one; the last move, by
+/// X, sends O back to the won top-left sub-board.
+fn position() -> GameState {
+    from_notation("OOO....../........./........./...XXX.../....O..../........./........./........./......... 1 -").unwrap()
+}
+
+fn last_move() -> Action {
+    Action::new(1, 1, 0, 0, false)
+}
+
+#[test]
+fn plain_rendering_shows_only_the_squares() {
+    let text = render(&GameState::new(), SYMBOLS, None, &RenderOptions::plain());
+    let lines: Vec<&str> = text.trim_matches('\n').lines().collect();
+
+    assert_eq!(lines.len(), 11);
+    assert_eq!(lines[0], " .  .  . | .  .  . | .  .  .");
+    assert_eq!(lines[3], "---------+---------+---------");
+}
+
+#[test]
+fn coordinates_label_both_axes() {
+    let text = render(&GameState::new(), SYMBOLS, None, &RenderOptions::plain().with_coordinates(true));
+    let lines: Vec<&str> = text.trim_matches('\n').lines().collect();
+
+    assert_eq!(lines[0], "    a  b  c   d  e  f   g  h  i");
+    assert!(lines[1].starts_with(" 1  . "));
+    assert!(lines[11].starts_with(" 9  . "));
+}
+
+#[test]
+fn won_sub_boards_are_covered() {
+    let options = RenderOptions::plain().with_overlays(true);
+    let lines: Vec<String> = render(&position(), SYMBOLS, None, &options).trim_matches('\n').lines().map(String::from).collect();
+
+    assert_eq!(&lines[0][..9], " O  O  O ");
+    assert_eq!(&lines[1][..9], " O     O ");
+    assert_eq!(&lines[4][10..19], " X     X ");
+    assert_eq!(&lines[5][10..19], "    X    ");
+
+    let uncovered = render(&position(), SYMBOLS, None, &RenderOptions::plain());
+    assert!(uncovered.trim_matches('\n').lines().nth(5).unwrap().starts_with(" .  .  . |    O    |"));
+}
+
+#[test]
+fn summary_shows_results_and_where_to_play() {
+    let text = render(&position(), SYMBOLS, Some(&last_move()), &RenderOptions::new());
+    let lines: Vec<&str> = text.trim_matches('\n').lines().collect();
+
+    assert!(lines[1].ends_with("Sub-boards"));
+    assert!(lines[2].ends_with("O * *"));
+    assert!(lines[3].ends_with("* X *"));
+    assert!(lines[4].ends_with("* * *"));
+}
+
+#[test]
+fn last_move_is_bracketed() {
+    let options = RenderOptions::plain().with_last_move(true);
+    let text = render(&position(), SYMBOLS, Some(&last_move()), &options);
+    assert!(text.contains("[X]"));
+
+    let text = render(&position(), SYMBOLS, Some(&last_move()), &options.with_overlays(true));
+    assert!(text.contains("[X]"));
+    assert_eq!(text.matches('[').count(), 1);
+}
+
+#[test]
+fn unicode_uses_box_drawing() {
+    let text = render(&GameState::new(), SYMBOLS, None, &RenderOptions::plain().with_unicode(true));
+
+    assert!(text.contains('│') && text.contains('┼') && text.contains('·'));
+    assert!(!text.contains('|'));
+}
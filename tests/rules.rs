@@ -0,0 +1,211 @@
+use ultimate_ttt::actor::{Actor, Context, Decision};
+use ultimate_ttt::ai::mcts::MCTSActor;
+use ultimate_ttt::game::action::Action;
+use ultimate_ttt::game::board::{BoardStructure, Owned, SubBoard};
+use ultimate_ttt::game::game_result::GameResult;
+use ultimate_ttt::game::game_state::GameState;
+use ultimate_ttt::game::notation::from_notation;
+use ultimate_ttt::game::record::GameRecord;
+use ultimate_ttt::game::rules::{ClosedBoardRule, Rules, WinCondition};
+
+use GameResult::{Draw, Player1Wins as O, Player2Wins as X};
+
+/// The move the actor decides on, asked outside any game.
+fn decide(actor: &mut dyn Actor, game_state: &GameState) -> Action {
+    let record = GameRecord::new(game_state.clone());
+    match actor.get_decision(game_state, &Context::new(&record)) {
+        Ok(Decision::Move(action)) => action,
+        other => panic!("expected a move, got {:?}", other),
+    }
+}
+
+fn position(notation: &str, rules: Rules) -> GameState {
+    let mut game_state = from_notation(notation).unwrap();
+    game_state.rules = rules;
+    game_state
+}
+
+fn macro_board(results: &[(usize, usize, GameResult)]) -> BoardStructure<SubBoard> {
+    let mut structure = <BoardStructure<SubBoard>>::new();
+    for &(x, y, result) in results {
+        structure.set_result_at(x, y, Some(result));
+    }
+    structure
+}
+
+// Player 2 is sent to the top-left sub-board, which player 1 has won and in
+// which player 2 holds two squares of the middle row.
+const SENT_TO_CLOSED: &str = "OOO....../XX......./........./...O...../........./........./........./........./........X 2 -";
+
+#[test]
+fn standard_rules_keep_closed_sub_boards_closed() {
+    let game_state = position(SENT_TO_CLOSED, Rules::new());
+
+    assert_eq!(game_state.possible_actions().len(), 70);
+    assert!(game_state.possible_actions().iter().all(|action| (action.sub_x, action.sub_y) != (0, 0)));
+}
+
+#[test]
+fn any_empty_square_allows_moves_in_closed_sub_boards() {
+    let rules = Rules::new().with_closed_board(ClosedBoardRule::AnyEmptySquare);
+    let mut game_state = position(SENT_TO_CLOSED, rules);
+
+    assert_eq!(game_state.possible_actions().len(), 74);
+    assert_eq!(game_state.possible_actions().iter().filter(|action| (action.sub_x, action.sub_y) == (0, 0)).count(), 4);
+
+    // Completing a line in a closed sub-board does not change its owner, but
+    // still sends the opponent on.
+    assert_eq!(game_state.make_move(0, 0, 2, 1), None);
+    assert_eq!(game_state.board().get(0, 0).result(), Some(O));
+    assert_eq!((game_state.current_sub_x, game_state.current_sub_y), (Some(2), Some(1)));
+}
+
+#[test]
+fn any_empty_square_only_applies_when_free() {
+    let rules = Rules::new().with_closed_board(ClosedBoardRule::AnyEmptySquare);
+    let game_state = position("OOO....../XX......./........./...O...../........./........./........./........./........X 2 4", rules);
+
+    assert_eq!(game_state.possible_actions().len(), 8);
+}
+
+#[test]
+fn drawn_sub_boards_count_for_both_players() {
+    let standard = Rules::new();
+    let both = Rules::new().with_draws_count_for_both(true);
+
+    let structure = macro_board(&[(0, 0, O), (1, 0, Draw), (2, 0, O)]);
+    assert_eq!(structure.check_result(1, 0, &standard), None);
+    assert_eq!(structure.check_result(1, 0, &both), Some(O));
+    assert_eq!(structure.check_result(0, 0, &both), Some(O));
+
+    let structure = macro_board(&[(0, 0, Draw), (1, 1, X), (2, 2, Draw)]);
+    assert_eq!(structure.check_result(1, 1, &both), Some(X));
+
+    let structure = macro_board(&[(0, 0, Draw), (1, 0, Draw), (2, 0, Draw)]);
+    assert_eq!(structure.check_result(1, 0, &both), None);
+
+    let structure = macro_board(&[(0, 0, O), (1, 0, X), (2, 0, Draw)]);
+    assert_eq!(structure.check_result(2, 0, &both), None);
+}
+
+#[test]
+fn drawn_sub_board_completing_lines_for_both_players_is_a_draw() {
+    let both = Rules::new().with_draws_count_for_both(true);
+    let structure = macro_board(&[(0, 0, O), (1, 0, Draw), (2, 0, O), (1, 1, X), (1, 2, X)]);
+
+    assert_eq!(structure.check_result(1, 0, &both), Some(Draw));
+}
+
+#[test]
+fn lines_for_both_players_are_not_broken_by_sub_board_count() {
+    // The drawn top-middle sub-board completes the top row for player 1 and
+    // the middle column for player 2 while sub-boards are still open. Player 1
+    // has won more sub-boards, but the tiebreak only settles full boards.
+    let rules = Rules::new().with_draws_count_for_both(true).with_sub_board_tiebreak(true);
+    let structure = macro_board(&[(0, 0, O), (1, 0, Draw), (2, 0, O), (1, 1, X), (1, 2, X), (0, 2, O)]);
+
+    assert_eq!(rules.game_result(&structure, 1, 0), Some(Draw));
+    assert_eq!(rules.with_win_condition(WinCondition::Misere).game_result(&structure, 1, 0), Some(Draw));
+}
+
+#[test]
+fn drawing_a_sub_board_can_complete_a_line() {
+    // Player 1 owns the top-left and top-right sub-boards and fills the last
+    // square of the top-middle one without a line.
+    let notation = "OOOOXOOOO/...OXX.../...XO..../X.....X.X/....X..../........./.X.X...../........./......... 1 1";
+
+    let mut standard = position(notation, Rules::new());
+    assert_eq!(standard.make_move(1, 0, 2, 2), None);
+    assert_eq!(standard.board().get(1, 0).result(), Some(Draw));
+
+    let mut both = position(notation, Rules::new().with_draws_count_for_both(true));
+    assert_eq!(both.make_move(1, 0, 2, 2), Some(O));
+}
+
+#[test]
+fn sub_board_count_breaks_ties() {
+    // Player 1 draws the last sub-board; they won four sub-boards against
+    // player 2's three, with no line for either.
+    let notation = "OOOXXXOOO/XX....XX./........./OOOXXXOXO/......OXX/......XOO/...OOOOXO/......OXX/XXX...XO. 1 8";
+
+    let mut standard = position(notation, Rules::new());
+    assert_eq!(standard.make_move(2, 2, 2, 2), Some(Draw));
+
+    let mut tiebreak = position(notation, Rules::new().with_sub_board_tiebreak(true));
+    assert_eq!(tiebreak.make_move(2, 2, 2, 2), Some(O));
+}
+
+#[test]
+fn equal_sub_board_counts_stay_drawn() {
+    let rules = Rules::new().with_sub_board_tiebreak(true);
+    let structure = macro_board(&[
+        (0, 0, O), (1, 0, X), (2, 0, O),
+        (0, 1, O), (1, 1, X), (2, 1, X),
+        (0, 2, X), (1, 2, O), (2, 2, Draw),
+    ]);
+
+    assert_eq!(rules.resolve_draw(&structure), Draw);
+}
+
+#[test]
+fn rules_survive_moves_and_copies() {
+    let rules = Rules::new().with_sub_board_tiebreak(true).with_closed_board(ClosedBoardRule::AnyEmptySquare);
+    let mut game_state = GameState::with_rules(rules);
+
+    game_state.make_move(1, 1, 0, 0);
+    assert_eq!(*game_state.clone().rules(), rules);
+}
+
+// Player 1 owns the two top-left sub-boards and is sent to the top-right one,
+// where completing the top row would also complete the top row of sub-boards.
+const LINE_IN_ONE: &str = "OOOOOOOO./........./........./XXX....../X......../X.X....../........./........./......... 1 2";
+
+#[test]
+fn completing_a_line_wins_or_loses_by_win_condition() {
+    let mut standard = position(LINE_IN_ONE, Rules::new());
+    assert_eq!(standard.make_move(2, 0, 2, 0), Some(O));
+
+    let mut misere = position(LINE_IN_ONE, Rules::new().with_win_condition(WinCondition::Misere));
+    assert_eq!(misere.make_move(2, 0, 2, 0), Some(X));
+
+    let mut most = position(LINE_IN_ONE, Rules::new().with_win_condition(WinCondition::MostSubBoards));
+    assert_eq!(most.make_move(2, 0, 2, 0), None);
+    assert_eq!(most.board().get(2, 0).result(), Some(O));
+}
+
+#[test]
+fn most_sub_boards_decides_full_boards() {
+    let notation = "OOOXXXOOO/XX....XX./........./OOOXXXOXO/......OXX/......XOO/...OOOOXO/......OXX/XXX...XO. 1 8";
+
+    let mut most = position(notation, Rules::new().with_win_condition(WinCondition::MostSubBoards));
+    assert_eq!(most.make_move(2, 2, 2, 2), Some(O));
+
+    let rules = Rules::new().with_win_condition(WinCondition::MostSubBoards);
+    let structure = macro_board(&[(0, 0, O), (1, 0, O), (2, 0, O), (0, 1, X)]);
+    assert_eq!(rules.game_result(&structure, 2, 0), None);
+}
+
+#[test]
+fn misere_reverses_the_tiebreak() {
+    let notation = "OOOXXXOOO/XX....XX./........./OOOXXXOXO/......OXX/......XOO/...OOOOXO/......OXX/XXX...XO. 1 8";
+
+    let mut misere = position(notation, Rules::new().with_win_condition(WinCondition::Misere));
+    assert_eq!(misere.make_move(2, 2, 2, 2), Some(Draw));
+
+    let rules = Rules::new().with_win_condition(WinCondition::Misere).with_sub_board_tiebreak(true);
+    let mut misere = position(notation, rules);
+    assert_eq!(misere.make_move(2, 2, 2, 2), Some(X));
+}
+
+#[test]
+fn mcts_avoids_completing_a_line_in_misere() {
+    let mut actor = MCTSActor::new(0).with_iterations(2000).with_endgame_threshold(0);
+
+    let standard = position(LINE_IN_ONE, Rules::new());
+    let action = decide(&mut actor, &standard);
+    assert_eq!((action.x, action.y), (2, 0));
+
+    let misere = position(LINE_IN_ONE, Rules::new().with_win_condition(WinCondition::Misere));
+    let action = decide(&mut actor, &misere);
+    assert_ne!((action.x, action.y), (2, 0));
+}
@@ -0,0 +1,351 @@
+use std::time::{Duration, Instant};
+
+use ultimate_ttt::actor::{Actor, ActorError, CancelToken, Context, Decision, SearchReport};
+use ultimate_ttt::ai::mcts::MCTSActor;
+use ultimate_ttt::game::action::Action;
+use ultimate_ttt::game::game_result::GameResult::Draw;
+use ultimate_ttt::game::game_state::GameState;
+use ultimate_ttt::game::player::Player;
+use ultimate_ttt::game::record::GameRecord;
+use ultimate_ttt::game::runner::GRACE;
+use ultimate_ttt::gui::{GameObserver, Match, Setup, Starter};
+
+/// Always plays the first legal move.
+struct FirstMove {}
+
+impl Actor for FirstMove {
+    fn get_decision(&mut self, game_state: &GameState, _context: &Context) -> Result<Decision, ActorError> {
+        Ok(Decision::Move(game_state.possible_actions()[0].clone()))
+    }
+}
+
+/// Makes the given decisions in order, then plays like `FirstMove`.
+struct Scripted {
+    decisions: Vec<Decision>,
+}
+
+impl Actor for Scripted {
+    fn get_decision(&mut self, game_state: &GameState, context: &Context) -> Result<Decision, ActorError> {
+        if self.decisions.is_empty() {
+            FirstMove {}.get_decision(game_state, context)
+        } else {
+            Ok(self.decisions.remove(0))
+        }
+    }
+}
+
+/// Writes down everything it is told.
+#[derive(Default)]
+struct Log {
+    events: Vec<String>,
+    moves: Vec<(Action, Duration)>,
+    reports: Vec<(Player, SearchReport)>,
+}
+
+impl GameObserver for Log {
+    fn game_started(&mut self, _game_state: &GameState, record: &GameRecord) {
+        self.events.push(format!("started {}", record.moves.len()));
+    }
+
+    fn turn_started(&mut self, player: Player, _game_state: &GameState, _record: &GameRecord) {
+        self.events.push(format!("turn {}", player.num()));
+    }
+
+    fn search_reported(&mut self, player: Player, report: &SearchReport) {
+        self.reports.push((player, report.clone()));
+    }
+
+    fn move_made(&mut self, action: &Action, elapsed: Duration, game_state: &GameState, record: &GameRecord) {
+        assert_eq!(record.moves.last(), Some(action));
+        assert!(*game_state == record.final_state());
+        self.events.push("moved".to_string());
+        self.moves.push((action.clone(), elapsed));
+    }
+
+    fn move_undone(&mut self, _game_state: &GameState, _record: &GameRecord) {
+        self.events.push("undone".to_string());
+    }
+
+    fn nothing_to_undo(&mut self, player: Player) {
+        self.events.push(format!("nothing to undo {}", player.num()));
+    }
+
+    fn resigned(&mut self, player: Player, _record: &GameRecord) {
+        self.events.push(format!("resigned {}", player.num()));
+    }
+
+    fn draw_offered(&mut self, player: Player, accepted: bool, _record: &GameRecord) {
+        self.events.push(format!("draw offered {} {}", player.num(), accepted));
+    }
+
+    fn actor_failed(&mut self, player: Player, error: &ActorError, _record: &GameRecord) {
+        self.events.push(format!("failed {} {}", player.num(), error));
+    }
+
+    fn game_ended(&mut self, _game_state: &GameState, record: &GameRecord) {
+        self.events.push(format!("ended {:?}", record.result));
+    }
+}
+
+#[test]
+fn observers_follow_the_game() {
+    let mut log = Log::default();
+    let mut first = Scripted { decisions: vec![Decision::Undo] };
+    let mut second = Scripted { decisions: vec![Decision::Resign] };
+
+    let record = Match::new(Setup::new())
+        .with_observer(&mut log)
+        .play(&mut first, &mut second);
+
+    assert_eq!(log.events, vec![
+        "started 0", "turn 1", "nothing to undo 1", "turn 1", "moved", "turn 2", "resigned 2", "ended Some(Player1Wins)",
+    ]);
+    assert_eq!(record.moves, vec![GameState::new().possible_actions()[0].clone()]);
+    assert!(log.reports.is_empty());
+}
+
+#[test]
+fn every_observer_sees_every_move() {
+    let (mut first_log, mut second_log) = (Log::default(), Log::default());
+
+    let record = Match::new(Setup::new())
+        .with_observer(&mut first_log)
+        .with_observer(&mut second_log)
+        .play(&mut FirstMove {}, &mut FirstMove {});
+
+    for log in [first_log, second_log].iter() {
+        let moves: Vec<Action> = log.moves.iter().map(|(action, _)| action.clone()).collect();
+        assert_eq!(moves, record.moves);
+        assert_eq!(log.events.last().unwrap(), &format!("ended {:?}", record.result));
+    }
+}
+
+#[test]
+fn searching_actors_report() {
+    let mut log = Log::default();
+    let mut engine = MCTSActor::new(0).with_iterations(200);
+    let mut quitter = Scripted { decisions: vec![Decision::Quit] };
+
+    Match::new(Setup::new()).with_observer(&mut log).play(&mut engine, &mut quitter);
+
+    assert_eq!(log.reports.len(), 1);
+    assert_eq!(log.reports[0].0, Player::Player1);
+    assert_eq!(log.reports[0].1.simulations, 200);
+    assert_eq!(log.events.last().unwrap(), "ended None");
+}
+
+struct Slow {}
+
+impl Actor for Slow {
+    fn get_decision(&mut self, game_state: &GameState, _context: &Context) -> Result<Decision, ActorError> {
+        std::thread::sleep(Duration::from_millis(20));
+        Ok(Decision::Move(game_state.possible_actions()[0].clone()))
+    }
+}
+
+#[test]
+fn moves_are_timed() {
+    let mut log = Log::default();
+    let mut resigner = Scripted { decisions: vec![Decision::Resign] };
+
+    Match::new(Setup::new()).with_observer(&mut log).play(&mut Slow {}, &mut resigner);
+
+    assert!(log.moves[0].1 >= Duration::from_millis(20));
+}
+
+#[test]
+fn delay_pauses_after_moves() {
+    let mut first = Scripted { decisions: vec![Decision::Move(GameState::new().possible_actions()[0].clone()), Decision::Resign] };
+
+    let start = Instant::now();
+    Match::new(Setup::new())
+        .with_delay(Duration::from_millis(30))
+        .play(&mut first, &mut FirstMove {});
+
+    assert!(start.elapsed() >= Duration::from_millis(60));
+}
+
+/// Plays the first legal move and answers draw offers as told, or fails.
+struct Answering {
+    answer: Result<bool, ActorError>,
+}
+
+impl Actor for Answering {
+    fn get_decision(&mut self, game_state: &GameState, _context: &Context) -> Result<Decision, ActorError> {
+        Ok(Decision::Move(game_state.possible_actions()[0].clone()))
+    }
+
+    fn accept_draw(&mut self, _game_state: &GameState, _context: &Context) -> Result<bool, ActorError> {
+        self.answer.clone()
+    }
+}
+
+#[test]
+fn draw_offers_are_answered_by_the_opponent() {
+    let mut log = Log::default();
+    let mut offering = Scripted { decisions: vec![Decision::OfferDraw, Decision::OfferDraw] };
+    let mut declining = Answering { answer: Ok(false) };
+
+    let record = Match::new(Setup::new()).with_observer(&mut log).play(&mut offering, &mut declining);
+    assert_eq!(&log.events[..4], &["started 0", "turn 1", "draw offered 1 false", "turn 1"]);
+    assert_eq!(log.events[4], "draw offered 1 false");
+    assert_ne!(record.result, Some(Draw));
+
+    let mut log = Log::default();
+    let mut offering = Scripted { decisions: vec![Decision::OfferDraw] };
+    let mut accepting = Answering { answer: Ok(true) };
+
+    let record = Match::new(Setup::new()).with_observer(&mut log).play(&mut offering, &mut accepting);
+    assert_eq!(log.events, vec!["started 0", "turn 1", "draw offered 1 true", "ended Some(Draw)"]);
+    assert_eq!(record.result, Some(Draw));
+    assert!(record.moves.is_empty());
+}
+
+#[test]
+fn failing_actors_end_the_game_without_a_result() {
+    let mut log = Log::default();
+    let mut offering = Scripted { decisions: vec![Decision::OfferDraw] };
+    let mut gone = Answering { answer: Err(ActorError::Disconnected("connection reset".to_string())) };
+
+    let record = Match::new(Setup::new()).with_observer(&mut log).play(&mut offering, &mut gone);
+
+    assert_eq!(log.events, vec!["started 0", "turn 1", "failed 2 disconnected: connection reset", "ended None"]);
+    assert_eq!(record.result, None);
+}
+
+#[test]
+fn illegal_moves_end_the_game_without_a_result() {
+    let mut log = Log::default();
+    let mut first = Scripted { decisions: vec![Decision::Move(Action::new(1, 1, 1, 1, true))] };
+    let mut second = Scripted { decisions: vec![Decision::Move(Action::new(1, 1, 1, 1, false))] };
+
+    let record = Match::new(Setup::new()).with_observer(&mut log).play(&mut first, &mut second);
+
+    assert_eq!(log.events, vec!["started 0", "turn 1", "moved", "turn 2", "failed 2 illegal move 1111", "ended None"]);
+    assert_eq!(record.moves.len(), 1);
+    assert_eq!(record.result, None);
+}
+
+#[test]
+fn cancelled_games_stop() {
+    let mut log = Log::default();
+    let cancel = CancelToken::new();
+    cancel.cancel();
+
+    let record = Match::new(Setup::new())
+        .with_cancel(cancel)
+        .with_observer(&mut log)
+        .play(&mut FirstMove {}, &mut FirstMove {});
+
+    assert_eq!(log.events, vec!["started 0", "ended None"]);
+    assert!(record.moves.is_empty());
+}
+
+#[test]
+fn engines_stop_for_the_clock_and_cancellation() {
+    let mut log = Log::default();
+    let mut engine = MCTSActor::new(60_000);
+    let mut quitter = Scripted { decisions: vec![Decision::Quit] };
+
+    let start = Instant::now();
+    Match::new(Setup::new())
+        .with_time_limit(Duration::from_millis(50))
+        .with_observer(&mut log)
+        .play(&mut engine, &mut quitter);
+    assert!(start.elapsed() < Duration::from_secs(10));
+    assert_eq!(log.moves.len(), 1);
+
+    let mut log = Log::default();
+    let cancel = CancelToken::new();
+    let canceller = cancel.clone();
+    let timer = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(50));
+        canceller.cancel();
+    });
+
+    let start = Instant::now();
+    let record = Match::new(Setup::new())
+        .with_cancel(cancel)
+        .with_observer(&mut log)
+        .play(&mut MCTSActor::new(60_000), &mut FirstMove {});
+    timer.join().unwrap();
+
+    assert!(start.elapsed() < Duration::from_secs(10));
+    assert_eq!(log.events, vec!["started 0", "turn 1", "failed 1 cancelled", "ended None"]);
+    assert!(record.moves.is_empty());
+}
+
+/// Plays the first legal move after thinking for a while.
+struct Late {
+    thinking: Duration,
+}
+
+impl Actor for Late {
+    fn get_decision(&mut self, game_state: &GameState, _context: &Context) -> Result<Decision, ActorError> {
+        std::thread::sleep(self.thinking);
+        Ok(Decision::Move(game_state.possible_actions()[0].clone()))
+    }
+}
+
+#[test]
+fn late_decisions_are_timeouts() {
+    let mut log = Log::default();
+    let record = Match::new(Setup::new())
+        .with_time_limit(Duration::from_millis(10))
+        .with_observer(&mut log)
+        .play(&mut Late { thinking: Duration::from_millis(10) + GRACE * 2 }, &mut FirstMove {});
+
+    assert_eq!(log.events, vec!["started 0", "turn 1", "failed 1 out of time", "ended None"]);
+    assert!(record.moves.is_empty());
+
+    let mut log = Log::default();
+    Match::new(Setup::new())
+        .with_time_limit(Duration::from_secs(10))
+        .with_observer(&mut log)
+        .play(&mut Late { thinking: Duration::from_millis(10) }, &mut Scripted { decisions: vec![Decision::Quit] });
+
+    assert_eq!(log.events, vec!["started 0", "turn 1", "moved", "turn 2", "ended None"]);
+}
+
+/// Plays the first legal move and writes down what it is told.
+#[derive(Default)]
+struct Listening {
+    events: Vec<String>,
+}
+
+impl Actor for Listening {
+    fn new_game(&mut self, game_state: &GameState, player: Player) {
+        self.events.push(format!("new game {} as {}", game_state.possible_actions().len(), player.num()));
+    }
+
+    fn get_decision(&mut self, game_state: &GameState, _context: &Context) -> Result<Decision, ActorError> {
+        self.events.push("moving".to_string());
+        Ok(Decision::Move(game_state.possible_actions()[0].clone()))
+    }
+
+    fn opponent_moved(&mut self, action: &Action, game_state: &GameState) {
+        assert!(game_state.possible_actions().iter().all(|possible| possible != action));
+        self.events.push("opponent moved".to_string());
+    }
+
+    fn game_over(&mut self, record: &GameRecord) {
+        self.events.push(format!("game over {:?}", record.result));
+    }
+}
+
+#[test]
+fn actors_follow_the_game() {
+    let mut first = Listening::default();
+    let mut second = Listening::default();
+    let mut resigner = Scripted { decisions: vec![Decision::Move(GameState::new().possible_actions()[0].clone()), Decision::Resign] };
+
+    Match::new(Setup::new().with_starter(Starter::Second)).play(&mut first, &mut resigner);
+    assert_eq!(first.events, vec!["new game 81 as 2", "opponent moved", "moving", "game over Some(Player2Wins)"]);
+
+    let record = Match::new(Setup::new()).play(&mut first, &mut second);
+    assert_eq!(first.events[4], "new game 81 as 1");
+    assert_eq!(second.events[0], "new game 81 as 2");
+    assert_eq!(second.events[1..3], ["opponent moved", "moving"]);
+    assert_eq!(first.events.last(), second.events.last());
+    assert_eq!(second.events.last().unwrap(), &format!("game over {:?}", record.result));
+}
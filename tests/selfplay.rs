@@ -0,0 +1,88 @@
+use ultimate_ttt::actor::{Actor, ActorError, Context, Decision};
+use ultimate_ttt::ai::selfplay::{PositionRecord, SelfPlay};
+use ultimate_ttt::game::action::Action;
+use ultimate_ttt::game::game_result::GameResult;
+use ultimate_ttt::game::game_state::GameState;
+use ultimate_ttt::game::notation::to_notation;
+
+/// Spreads its policy evenly over the legal moves.
+struct Uniform {}
+
+impl Actor for Uniform {
+    fn get_decision(&mut self, game_state: &GameState, _context: &Context) -> Result<Decision, ActorError> {
+        Ok(Decision::Move(game_state.possible_actions()[0].clone()))
+    }
+
+    fn get_policy(&mut self, game_state: &GameState, _context: &Context) -> Result<Vec<(Action, f32)>, ActorError> {
+        let actions = game_state.possible_actions();
+        let probability = 1. / actions.len() as f32;
+        Ok(actions.into_iter().map(|action| (action, probability)).collect())
+    }
+}
+
+fn run(seed: u64) -> (String, usize) {
+    let mut output = Vec::new();
+    let stats = SelfPlay::new(3)
+        .with_random_plies(4)
+        .with_temperature(1., 20)
+        .with_seed(seed)
+        .run(&mut Uniform {}, &mut output)
+        .unwrap();
+
+    assert_eq!(stats.player1_wins + stats.player2_wins + stats.draws, 3);
+    (String::from_utf8(output).unwrap(), stats.positions)
+}
+
+fn squares(policy: &[(Action, f32)]) -> Vec<(usize, usize, usize, usize, f32)> {
+    policy.iter().map(|(action, probability)| (action.sub_x, action.sub_y, action.x, action.y, *probability)).collect()
+}
+
+#[test]
+fn position_records_survive_a_round_trip() {
+    let mut game_state = GameState::new();
+    Action::new(1, 1, 2, 0, true).apply(&mut game_state);
+    let policy = vec![(Action::new(2, 0, 0, 0, false), 0.75), (Action::new(2, 0, 1, 1, false), 0.25)];
+    let record = PositionRecord { game_state, policy, result: GameResult::Player2Wins };
+
+    let line = record.to_line();
+    let read = PositionRecord::from_line(&line).unwrap();
+    assert_eq!(to_notation(&read.game_state), to_notation(&record.game_state));
+    assert_eq!(squares(&read.policy), squares(&record.policy));
+    assert_eq!(read.result, record.result);
+    assert_eq!(read.to_line(), line);
+}
+
+#[test]
+fn malformed_lines_are_rejected() {
+    assert!(PositionRecord::from_line("").is_err());
+    assert!(PositionRecord::from_line(&format!("{}\t=\t81:1", to_notation(&GameState::new()))).is_err());
+}
+
+#[test]
+fn self_play_starts_from_the_given_position() {
+    let mut initial_state = GameState::new();
+    Action::new(1, 1, 0, 2, true).apply(&mut initial_state);
+
+    let mut output = Vec::new();
+    SelfPlay::new(2).with_initial_state(initial_state.clone()).run(&mut Uniform {}, &mut output).unwrap();
+
+    let output = String::from_utf8(output).unwrap();
+    let starts = output.lines().filter(|line| line.starts_with(&to_notation(&initial_state))).count();
+    assert_eq!(starts, 2);
+    assert!(!output.contains(&to_notation(&GameState::new())));
+}
+
+#[test]
+fn seeded_self_play_is_repeatable() {
+    let (output, positions) = run(7);
+    assert_eq!(output, run(7).0);
+
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines.len(), positions);
+    for line in lines {
+        let record = PositionRecord::from_line(line).unwrap();
+        let total: f32 = record.policy.iter().map(|(_, probability)| probability).sum();
+        assert!((total - 1.).abs() < 1e-4);
+        assert_eq!(record.policy.len(), record.game_state.possible_actions().len());
+    }
+}
@@ -0,0 +1,71 @@
+use ultimate_ttt::game::action::Action;
+use ultimate_ttt::game::game_state::GameState;
+use ultimate_ttt::game::notation::to_notation;
+use ultimate_ttt::game::symmetry::{canonicalize, Symmetry, SYMMETRIES};
+
+fn position() -> GameState {
+    let mut game_state = GameState::new();
+    for action in [Action::new(0, 0, 1, 0, true), Action::new(1, 0, 2, 2, false), Action::new(2, 2, 0, 1, false)].iter() {
+        action.apply(&mut game_state);
+    }
+
+    game_state
+}
+
+#[test]
+fn symmetries_permute_the_squares() {
+    for &symmetry in SYMMETRIES.iter() {
+        let mut images = Vec::new();
+        for y in 0..3 {
+            for x in 0..3 {
+                let (image_x, image_y) = symmetry.apply(x, y, 3);
+                assert_eq!(symmetry.inverse().apply(image_x, image_y, 3), (x, y), "{:?}", symmetry);
+                images.push((image_x, image_y));
+            }
+        }
+
+        images.sort();
+        images.dedup();
+        assert_eq!(images.len(), 9);
+        assert_eq!(symmetry.apply(1, 1, 3), (1, 1));
+    }
+}
+
+#[test]
+fn rotations_turn_clockwise() {
+    assert_eq!(Symmetry::Rotate90.apply(0, 0, 3), (2, 0));
+    assert_eq!(Symmetry::Rotate90.apply(2, 0, 3), (2, 2));
+    assert_eq!(Symmetry::Rotate180.apply(1, 0, 3), (1, 2));
+    assert_eq!(Symmetry::FlipDiagonal.apply(2, 0, 3), (0, 2));
+
+    let action = Symmetry::Rotate90.transform_action(&Action::new(0, 0, 1, 0, true), 3);
+    assert_eq!((action.sub_x, action.sub_y, action.x, action.y), (2, 0, 2, 1));
+}
+
+#[test]
+fn moves_map_to_moves_of_the_image() {
+    let game_state = position();
+
+    for &symmetry in SYMMETRIES.iter() {
+        let image = symmetry.transform_state(&game_state);
+        let key = |action: &Action| (action.sub_x, action.sub_y, action.x, action.y, action.full_board);
+        let mut expected: Vec<_> = game_state.possible_actions().iter().map(|action| key(&symmetry.transform_action(action, 3))).collect();
+        let mut actual: Vec<_> = image.possible_actions().iter().map(key).collect();
+
+        expected.sort();
+        actual.sort();
+        assert_eq!(actual, expected, "{:?}", symmetry);
+    }
+}
+
+#[test]
+fn every_image_has_the_same_canonical_form() {
+    let game_state = position();
+    let (canonical, symmetry) = canonicalize(&game_state);
+    assert_eq!(to_notation(&symmetry.transform_state(&game_state)), to_notation(&canonical));
+
+    for &symmetry in SYMMETRIES.iter() {
+        let (image_canonical, _) = canonicalize(&symmetry.transform_state(&game_state));
+        assert_eq!(to_notation(&image_canonical), to_notation(&canonical), "{:?}", symmetry);
+    }
+}
@@ -0,0 +1,135 @@
+use ultimate_ttt::actor::{Actor, ActorError, Context, Decision};
+use ultimate_ttt::ai::baseline::{GreedyActor, RandomActor};
+use ultimate_ttt::ai::tournament::{elo, expected_points, Score, Sprt, Tournament, Verdict};
+use ultimate_ttt::game::game_state::GameState;
+use ultimate_ttt::game::player::PlayerConfig;
+use ultimate_ttt::gui::Setup;
+
+fn setup() -> Setup {
+    Setup::new().with_players(PlayerConfig::new("Greedy", 'G'), PlayerConfig::new("Random", 'R'))
+}
+
+fn greedy() -> Box<dyn Actor> {
+    Box::new(GreedyActor::new().with_seed(1))
+}
+
+fn random() -> Box<dyn Actor> {
+    Box::new(RandomActor::new().with_seed(2))
+}
+
+#[test]
+fn elo_follows_the_points() {
+    let even = Score { wins: 10, draws: 10, losses: 10, unfinished: 0 };
+    assert_eq!(even.points(), 0.5);
+    assert!(even.elo().0.abs() < 1e-9);
+
+    let (difference, error) = Score { wins: 30, draws: 0, losses: 10, unfinished: 0 }.elo();
+    assert!((difference - 190.85).abs() < 0.01);
+    assert!(error > 0. && error.is_finite());
+
+    let (_, wider) = Score { wins: 3, draws: 0, losses: 1, unfinished: 0 }.elo();
+    assert!(wider > error);
+
+    for &points in [0.1, 0.5, 0.75, 0.99].iter() {
+        assert!((expected_points(elo(points)) - points).abs() < 1e-9);
+    }
+    assert_eq!(Score { wins: 5, draws: 0, losses: 0, unfinished: 0 }.elo().0, f64::INFINITY);
+}
+
+#[test]
+fn sprt_decides_once_the_evidence_is_clear() {
+    let sprt = Sprt::new(0., 50.);
+
+    assert_eq!(sprt.verdict(&Score { wins: 3, draws: 2, losses: 2, unfinished: 0 }), Verdict::Continue);
+    assert_eq!(sprt.verdict(&Score { wins: 100, draws: 100, losses: 100, unfinished: 0 }), Verdict::AcceptH0);
+    assert_eq!(sprt.verdict(&Score { wins: 150, draws: 100, losses: 50, unfinished: 0 }), Verdict::AcceptH1);
+    assert_eq!(sprt.llr(&Score::default()), 0.);
+}
+
+#[test]
+fn colours_alternate_over_shared_openings() {
+    let report = Tournament::new(setup(), 6)
+        .with_random_openings(3)
+        .with_seed(5)
+        .run(&greedy, &random, &mut |_, _| {})
+        .unwrap();
+
+    assert_eq!(report.records.len(), 6);
+    assert_eq!(report.score.games(), 6);
+    assert_eq!(report.verdict, None);
+
+    for (game, pair) in report.records.chunks(2).enumerate() {
+        assert!(pair[0].initial_state == pair[1].initial_state, "pair {} has different openings", game);
+        assert_ne!(pair[0].initial_state.possible_actions(), GameState::new().possible_actions());
+        let to_move = pair[0].initial_state.current_player();
+        assert_eq!(pair[0].player(to_move).name, "Greedy");
+        assert_eq!(pair[1].player(to_move).name, "Random");
+    }
+}
+
+#[test]
+fn parallel_games_are_all_counted() {
+    let mut seen = 0;
+    let report = Tournament::new(setup(), 9)
+        .with_threads(3)
+        .run(&greedy, &random, &mut |record, score| {
+            seen += 1;
+            assert!(record.result.is_some());
+            assert_eq!(score.games(), seen);
+        })
+        .unwrap();
+
+    assert_eq!(seen, 9);
+    assert_eq!(report.records.len(), 9);
+    assert!(report.score.wins > report.score.losses);
+}
+
+#[test]
+fn sprt_stops_the_tournament_early() {
+    let report = Tournament::new(setup(), 200)
+        .with_threads(2)
+        .with_random_openings(2)
+        .with_sprt(Sprt::new(0., 100.))
+        .run(&greedy, &random, &mut |_, _| {})
+        .unwrap();
+
+    assert_eq!(report.verdict, Some(Verdict::AcceptH1));
+    assert!(report.score.games() < 200);
+    assert_eq!(report.records.len(), report.score.games());
+}
+
+/// Fails as soon as it is asked for a move.
+struct Broken {}
+
+impl Actor for Broken {
+    fn get_decision(&mut self, _game_state: &GameState, _context: &Context) -> Result<Decision, ActorError> {
+        Err(ActorError::Failed("broken".to_string()))
+    }
+}
+
+#[test]
+fn unfinished_games_are_counted_apart() {
+    let broken = || Box::new(Broken {}) as Box<dyn Actor>;
+    let mut seen = 0;
+    let report = Tournament::new(setup(), 4)
+        .run(&broken, &random, &mut |record, score| {
+            seen += 1;
+            assert_eq!(record.result, None);
+            assert_eq!(score.unfinished, seen);
+        })
+        .unwrap();
+
+    assert_eq!(seen, 4);
+    assert_eq!(report.records.len(), 4);
+    assert_eq!(report.score, Score { wins: 0, draws: 0, losses: 0, unfinished: 4 });
+}
+
+#[test]
+fn openings_that_always_end_the_game_are_an_error() {
+    let result = Tournament::new(setup(), 2)
+        .with_random_openings(81)
+        .with_seed(1)
+        .run(&greedy, &random, &mut |_, _| panic!("no game should be played"));
+
+    assert!(result.is_err());
+}
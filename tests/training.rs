@@ -0,0 +1,54 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use ultimate_ttt::ai::nn::{Network, CELLS};
+use ultimate_ttt::ai::selfplay::PositionRecord;
+use ultimate_ttt::ai::training::{samples, Trainer};
+use ultimate_ttt::game::action::Action;
+use ultimate_ttt::game::game_result::GameResult;
+use ultimate_ttt::game::game_state::GameState;
+
+/// Records of a few positions, each with its first legal move as the policy.
+fn records() -> Vec<PositionRecord> {
+    let mut game_state = GameState::new();
+    let mut records = Vec::new();
+
+    for (index, action) in [Action::new(1, 1, 2, 0, true), Action::new(2, 0, 0, 1, false), Action::new(0, 1, 1, 1, false)].iter().enumerate() {
+        let policy = vec![(game_state.possible_actions()[0].clone(), 1.)];
+        let result = if index % 2 == 0 { GameResult::Player1Wins } else { GameResult::Draw };
+        records.push(PositionRecord { game_state: game_state.clone(), policy, result });
+        action.apply(&mut game_state);
+    }
+
+    records
+}
+
+#[test]
+fn augmenting_adds_every_symmetric_image() {
+    let records = records();
+
+    assert_eq!(samples(&records, false).len(), records.len());
+
+    let augmented = samples(&records, true);
+    assert_eq!(augmented.len(), 8 * records.len());
+    for sample in augmented.iter() {
+        assert_eq!(sample.policy.iter().sum::<f32>(), 1.);
+        assert_eq!(sample.policy.len(), CELLS);
+    }
+}
+
+#[test]
+fn training_lowers_the_loss_on_a_fixed_batch() {
+    let network = Network::random(&[32], &mut StdRng::seed_from_u64(3));
+    let mut trainer = Trainer::new(network).with_learning_rate(0.01);
+    let batch = samples(&records(), false);
+
+    let (policy_before, value_before) = trainer.train_batch(&batch);
+    for _ in 0..50 {
+        trainer.train_batch(&batch);
+    }
+    let (policy_after, value_after) = trainer.train_batch(&batch);
+
+    assert!(policy_after < policy_before / 2., "policy loss {} -> {}", policy_before, policy_after);
+    assert!(value_after < value_before / 2., "value loss {} -> {}", value_before, value_after);
+}
@@ -0,0 +1,115 @@
+#![cfg(feature = "tui")]
+
+use crossterm::event::KeyCode;
+
+use ultimate_ttt::actor::{Decision, SearchReport};
+use ultimate_ttt::game::action::Action;
+use ultimate_ttt::game::game_state::GameState;
+use ultimate_ttt::game::notation::from_notation;
+use ultimate_ttt::game::player::{Player, PlayerConfig};
+use ultimate_ttt::game::record::GameRecord;
+use ultimate_ttt::gui::tui::Screen;
+
+fn forced() -> GameState {
+    from_notation("........./........./........./........./....O..../........./........./........./......... 2 4").unwrap()
+}
+
+#[test]
+fn cursor_moves_within_the_grid() {
+    let game_state = GameState::new();
+    let mut screen = Screen::new();
+
+    for key in [KeyCode::Left, KeyCode::Up].iter() {
+        assert_eq!(screen.handle_key(*key, &game_state), None);
+    }
+    assert_eq!(screen.cursor, (0, 0));
+
+    for _ in 0..12 {
+        screen.handle_key(KeyCode::Right, &game_state);
+        screen.handle_key(KeyCode::Down, &game_state);
+    }
+    assert_eq!(screen.cursor, (8, 8));
+}
+
+#[test]
+fn focus_moves_the_cursor_into_the_forced_sub_board() {
+    let mut screen = Screen::new();
+    screen.focus(&forced());
+    assert_eq!(screen.cursor, (4, 4));
+
+    screen.cursor = (5, 3);
+    screen.focus(&forced());
+    assert_eq!(screen.cursor, (5, 3));
+
+    screen.focus(&GameState::new());
+    assert_eq!(screen.cursor, (5, 3));
+}
+
+#[test]
+fn enter_plays_only_legal_squares() {
+    let game_state = forced();
+    let mut screen = Screen::new();
+
+    screen.cursor = (0, 0);
+    assert_eq!(screen.handle_key(KeyCode::Enter, &game_state), None);
+    assert_eq!(screen.message, "You have to play in sub-board 1 1.");
+
+    screen.cursor = (4, 4);
+    assert_eq!(screen.handle_key(KeyCode::Enter, &game_state), None);
+    assert!(!screen.message.is_empty());
+
+    screen.cursor = (5, 3);
+    assert_eq!(screen.handle_key(KeyCode::Char(' '), &game_state), Some(Decision::Move(Action::new(1, 1, 2, 0, false))));
+    assert!(screen.message.is_empty());
+}
+
+#[test]
+fn commands_have_keys() {
+    let game_state = GameState::new();
+    let mut screen = Screen::new();
+
+    assert_eq!(screen.handle_key(KeyCode::Char('u'), &game_state), Some(Decision::Undo));
+    assert_eq!(screen.handle_key(KeyCode::Char('r'), &game_state), Some(Decision::Resign));
+    assert_eq!(screen.handle_key(KeyCode::Char('q'), &game_state), Some(Decision::Quit));
+    assert_eq!(screen.handle_key(KeyCode::Esc, &game_state), Some(Decision::Quit));
+}
+
+#[test]
+fn screen_shows_players_and_moves() {
+    let mut record = GameRecord::new(GameState::new()).with_players(PlayerConfig::new("Alice", 'A'), PlayerConfig::new("Bob", 'B'));
+    let mut game_state = GameState::new();
+    for _ in 0..3 {
+        let action = game_state.possible_actions()[0].clone();
+        let result = action.apply(&mut game_state);
+        record.push(action, result);
+    }
+
+    let mut out = Vec::new();
+    Screen::new().draw(&mut out, &game_state, &record, "Bob's move (B)", true).unwrap();
+    let text = String::from_utf8(out).unwrap();
+
+    for expected in ["Alice", "Bob", "Bob's move (B)", "1. 0000 0010", "2. 1000"].iter() {
+        assert!(text.contains(expected), "{}", expected);
+    }
+}
+
+#[test]
+fn evaluation_bar_follows_the_last_search() {
+    let game_state = GameState::new();
+    let record = GameRecord::new(game_state.clone());
+    let mut screen = Screen::new();
+    let draw = |screen: &Screen| {
+        let mut out = Vec::new();
+        screen.draw(&mut out, &game_state, &record, "", false).unwrap();
+        String::from_utf8(out).unwrap()
+    };
+
+    screen.show_report(Player::Player2, &SearchReport { simulations: 100, expected: 0.8, proven: None });
+    assert!(draw(&screen).contains(" 20%"));
+
+    screen.show_report(Player::Player1, &SearchReport { simulations: 100, expected: 0.73, proven: None });
+    assert!(draw(&screen).contains(" 73%"));
+
+    screen.forget_report();
+    assert!(!draw(&screen).contains(" 73%"));
+}